@@ -44,6 +44,8 @@ pub enum AxisScaling {
 pub struct AppConfigFields {
     pub update_rate: u64,
     pub temperature_type: temperature::TemperatureType,
+    pub virtual_sensors: Vec<temperature::VirtualSensor>,
+    pub name_rules: Vec<temperature::NameRule>,
     pub use_dot: bool,
     pub cpu_left_legend: bool,
     pub show_average_cpu: bool, // TODO: Unify this in CPU options
@@ -190,9 +192,10 @@ impl App {
         }
         {
             let data = &self.converted_data.temp_data;
+            let warning = self.converted_data.temp_warning.as_deref();
             for temp in self.states.temp_state.widget_states.values_mut() {
                 if temp.force_update_data {
-                    temp.set_table_data(data);
+                    temp.set_table_data(data, warning);
                     temp.force_update_data = false;
                 }
             }
@@ -124,6 +124,7 @@ pub struct DataCollection {
     pub io_labels_and_prev: Vec<((u64, u64), (u64, u64))>,
     pub io_labels: Vec<(String, String)>,
     pub temp_harvest: Vec<temperature::TempHarvest>,
+    pub temp_warning: Option<String>,
     #[cfg(feature = "battery")]
     pub battery_harvest: Vec<batteries::BatteryHarvest>,
     #[cfg(feature = "zfs")]
@@ -150,6 +151,7 @@ impl Default for DataCollection {
             io_labels_and_prev: Vec::default(),
             io_labels: Vec::default(),
             temp_harvest: Vec::default(),
+            temp_warning: None,
             #[cfg(feature = "battery")]
             battery_harvest: Vec::default(),
             #[cfg(feature = "zfs")]
@@ -172,6 +174,7 @@ impl DataCollection {
         self.io_harvest = disks::IoHarvest::default();
         self.io_labels_and_prev = Vec::default();
         self.temp_harvest = Vec::default();
+        self.temp_warning = None;
         #[cfg(feature = "battery")]
         {
             self.battery_harvest = Vec::default();
@@ -250,7 +253,7 @@ impl DataCollection {
 
         // Temp
         if let Some(temperature_sensors) = harvested_data.temperature_sensors {
-            self.eat_temp(temperature_sensors);
+            self.eat_temp(temperature_sensors, harvested_data.temperature_warning);
         }
 
         // Disks
@@ -332,8 +335,11 @@ impl DataCollection {
         self.load_avg_harvest = load_avg;
     }
 
-    fn eat_temp(&mut self, temperature_sensors: Vec<temperature::TempHarvest>) {
+    fn eat_temp(
+        &mut self, temperature_sensors: Vec<temperature::TempHarvest>, warning: Option<String>,
+    ) {
         self.temp_harvest = temperature_sensors;
+        self.temp_warning = warning;
     }
 
     fn eat_disks(
@@ -398,6 +398,21 @@ pub(crate) const CONFIG_TEXT: &str = r#"# This is a default config file for bott
 # Whether to be require matching the whole word. Defaults to false.
 #whole_word = false
 
+# Virtual sensors are computed from the readings of other sensors, by name. The aggregation
+# can be one of "max", "min", "avg", or "delta" (the first source minus the second).
+#[[temperature.virtual_sensors]]
+#name = "CPU max"
+#sources = ["CPU: Tccd1", "CPU: Tccd2"]
+#aggregation = "max"
+
+# Name rules set the prefix of sensor names by the device they're on, and are checked in order
+# before the built-in names. `match` is plain text unless `regex` is true, and is case-insensitive.
+# These only apply when using lm_sensors.
+#[[temperature.name_rules]]
+#match = "nct6798"
+#label = "MB"
+#regex = false
+
 # Network widget configuration
 #[network]
 
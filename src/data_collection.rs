@@ -37,6 +37,11 @@ pub struct Data {
     pub cache: Option<memory::MemHarvest>,
     pub swap: Option<memory::MemHarvest>,
     pub temperature_sensors: Option<Vec<temperature::TempHarvest>>,
+    /// The backend that collected `temperature_sensors`.
+    pub temperature_backend: Option<temperature::TemperatureBackend>,
+    /// A warning from collecting `temperature_sensors`, e.g. if some sensors
+    /// failed to be read but others weren't.
+    pub temperature_warning: Option<String>,
     pub network: Option<network::NetworkHarvest>,
     pub list_of_processes: Option<Vec<processes::ProcessHarvest>>,
     pub disks: Option<Vec<disks::DiskHarvest>>,
@@ -47,6 +52,8 @@ pub struct Data {
     pub arc: Option<memory::MemHarvest>,
     #[cfg(feature = "gpu")]
     pub gpu: Option<Vec<(String, memory::MemHarvest)>>,
+    #[cfg(feature = "nvidia")]
+    pub gpu_fans: Option<Vec<temperature::PwmHarvest>>,
 }
 
 impl Default for Data {
@@ -60,6 +67,8 @@ impl Default for Data {
             cache: None,
             swap: None,
             temperature_sensors: None,
+            temperature_backend: None,
+            temperature_warning: None,
             list_of_processes: None,
             disks: None,
             io: None,
@@ -70,6 +79,8 @@ impl Default for Data {
             arc: None,
             #[cfg(feature = "gpu")]
             gpu: None,
+            #[cfg(feature = "nvidia")]
+            gpu_fans: None,
         }
     }
 }
@@ -78,6 +89,8 @@ impl Data {
     pub fn cleanup(&mut self) {
         self.io = None;
         self.temperature_sensors = None;
+        self.temperature_backend = None;
+        self.temperature_warning = None;
         self.list_of_processes = None;
         self.disks = None;
         self.memory = None;
@@ -96,6 +109,10 @@ impl Data {
         {
             self.gpu = None;
         }
+        #[cfg(feature = "nvidia")]
+        {
+            self.gpu_fans = None;
+        }
     }
 }
 
@@ -142,6 +159,9 @@ pub struct DataCollector {
     pub data: Data,
     sys: SysinfoSource,
     temperature_type: TemperatureType,
+    virtual_sensors: Vec<temperature::VirtualSensor>,
+    #[cfg(feature = "lmsensors")]
+    sensor_cache: temperature::SensorCache,
     use_current_cpu_total: bool,
     unnormalized_cpu: bool,
     last_collection_time: Instant,
@@ -188,6 +208,12 @@ impl DataCollector {
             #[cfg(target_os = "linux")]
             prev_non_idle: 0_f64,
             temperature_type: TemperatureType::Celsius,
+            virtual_sensors: Vec::new(),
+            #[cfg(feature = "lmsensors")]
+            sensor_cache: temperature::SensorCache::new(
+                temperature::MIN_SENSORS_INTERVAL,
+                filters.temp_filter.clone(),
+            ),
             use_current_cpu_total: false,
             unnormalized_cpu: false,
             last_collection_time,
@@ -242,6 +268,16 @@ impl DataCollector {
         self.temperature_type = temperature_type;
     }
 
+    pub fn set_virtual_sensors(&mut self, virtual_sensors: Vec<temperature::VirtualSensor>) {
+        self.virtual_sensors = virtual_sensors;
+    }
+
+    #[cfg_attr(not(feature = "lmsensors"), allow(unused_variables))]
+    pub fn set_name_rules(&mut self, name_rules: Vec<temperature::NameRule>) {
+        #[cfg(feature = "lmsensors")]
+        self.sensor_cache.set_name_rules(name_rules);
+    }
+
     pub fn set_use_current_cpu_total(&mut self, use_current_cpu_total: bool) {
         self.use_current_cpu_total = use_current_cpu_total;
     }
@@ -335,6 +371,9 @@ impl DataCollector {
         #[cfg(feature = "gpu")]
         self.update_gpus(); // update_gpus before procs for gpu_pids but after temps for appending
 
+        self.update_virtual_temps(); // after all other temps, as they're computed from them
+        self.stamp_temps(); // after all temps, so every backend's are stamped
+
         self.update_processes();
         self.update_network_usage();
         self.update_disks();
@@ -368,6 +407,11 @@ impl DataCollector {
                     self.gpus_total_mem = Some(proc.0);
                 }
             }
+
+            #[cfg(feature = "nvidia")]
+            if self.widgets_to_harvest.use_temp {
+                self.data.gpu_fans = Some(nvidia::get_nvidia_fan_speeds(&self.filters.temp_filter));
+            }
         }
     }
 
@@ -399,28 +443,84 @@ impl DataCollector {
     #[inline]
     fn update_temps(&mut self) {
         if self.widgets_to_harvest.use_temp {
-            #[cfg(not(target_os = "linux"))]
-            if let Ok(data) = temperature::get_temperature_data(
-                &self.sys.temps,
-                &self.temperature_type,
-                &self.filters.temp_filter,
-            ) {
-                self.data.temperature_sensors = data;
-            }
-
+            // If lm_sensors isn't available (e.g. `sensors` isn't installed) or fails
+            // to run, fall back to the platform's backend. `sensors` is run at most
+            // once every `MIN_SENSORS_INTERVAL`, however often this is called.
             #[cfg(feature = "lmsensors")]
-            if let Ok(data) =
-                temperature::get_temperature_data(&self.temperature_type, &self.filters.temp_filter)
-            {
-                self.data.temperature_sensors = data;
+            if let Ok(mut bundle) = self.sensor_cache.get().cloned() {
+                bundle.convert_temperatures(self.temperature_type);
+                self.data.temperature_sensors = Some(bundle.temperatures);
+                self.data.temperature_warning = bundle.warning;
+                self.data.temperature_backend = Some(temperature::TemperatureBackend::LmSensors);
+            } else {
+                self.update_platform_temps();
             }
 
-            #[cfg(target_os = "linux")]
             #[cfg(not(feature = "lmsensors"))]
-            if let Ok(data) =
-                temperature::get_temperature_data(&self.temperature_type, &self.filters.temp_filter)
-            {
-                self.data.temperature_sensors = data;
+            self.update_platform_temps();
+
+            #[cfg(target_os = "linux")]
+            if let Some(sensors) = &mut self.data.temperature_sensors {
+                temperature::append_power_supply_temperatures(
+                    sensors,
+                    &self.temperature_type,
+                    &self.filters.temp_filter,
+                    None,
+                );
+            }
+        }
+    }
+
+    /// Collects temperatures with the platform's own backend.
+    #[inline]
+    fn update_platform_temps(&mut self) {
+        #[cfg(all(target_os = "windows", feature = "windows"))]
+        if let Ok(Some(data)) = temperature::libre_hardware_monitor::get_temperature_data(
+            &self.temperature_type,
+            &self.filters.temp_filter,
+            None,
+        ) {
+            self.data.temperature_sensors = Some(data);
+            self.data.temperature_backend =
+                Some(temperature::TemperatureBackend::LibreHardwareMonitor);
+            return;
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        if let Ok(data) = temperature::sysinfo::get_temperature_data(
+            &self.sys.temps,
+            &self.temperature_type,
+            &self.filters.temp_filter,
+        ) {
+            self.data.temperature_sensors = data;
+            self.data.temperature_backend = Some(temperature::TemperatureBackend::Sysinfo);
+        }
+
+        #[cfg(target_os = "linux")]
+        if let Ok(data) = temperature::linux::get_temperature_data(
+            &self.temperature_type,
+            &self.filters.temp_filter,
+        ) {
+            self.data.temperature_sensors = data;
+            self.data.temperature_backend = Some(temperature::TemperatureBackend::Hwmon);
+        }
+    }
+
+    #[inline]
+    fn update_virtual_temps(&mut self) {
+        if let Some(sensors) = &mut self.data.temperature_sensors {
+            temperature::append_virtual_sensors(sensors, &self.virtual_sensors);
+        }
+    }
+
+    /// Gives every temperature that wasn't timestamped by its backend the same
+    /// timestamp. lm_sensors readings keep their own, as they may be cached.
+    #[inline]
+    fn stamp_temps(&mut self) {
+        if let Some(sensors) = &mut self.data.temperature_sensors {
+            let timestamp = Instant::now();
+            for temp in sensors {
+                temp.timestamp.get_or_insert(timestamp);
             }
         }
     }
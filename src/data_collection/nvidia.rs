@@ -2,14 +2,19 @@ use std::sync::OnceLock;
 
 use hashbrown::HashMap;
 use nvml_wrapper::{
-    enum_wrappers::device::TemperatureSensor, enums::device::UsedGpuMemory, error::NvmlError, Nvml,
+    enum_wrappers::device::{TemperatureSensor, TemperatureThreshold},
+    enums::device::{SampleValue, UsedGpuMemory},
+    error::NvmlError,
+    structs::device::FieldId,
+    sys_exports::field_id::NVML_FI_DEV_MEMORY_TEMP,
+    Device, Nvml,
 };
 
 use crate::{
     app::{filter::Filter, layout_manager::UsedWidgets},
     data_collection::{
         memory::MemHarvest,
-        temperature::{TempHarvest, TemperatureType},
+        temperature::{PwmHarvest, TempHarvest, TemperatureType},
     },
 };
 
@@ -21,6 +26,106 @@ pub struct GpusData {
     pub procs: Option<(u64, Vec<HashMap<u32, (u64, u32)>>)>,
 }
 
+/// Returns the GPU's memory temperature, in Celsius, if it reports one. Only
+/// some cards (e.g. ones with HBM) do.
+fn memory_temperature(device: &Device<'_>) -> Option<f32> {
+    let sample = device
+        .field_values_for(&[FieldId(NVML_FI_DEV_MEMORY_TEMP)])
+        .ok()?
+        .into_iter()
+        .next()?
+        .ok()?;
+
+    match sample.value.ok()? {
+        SampleValue::U32(value) => Some(value as f32),
+        SampleValue::U64(value) => Some(value as f32),
+        SampleValue::I64(value) => Some(value as f32),
+        SampleValue::F64(value) => Some(value as f32),
+    }
+}
+
+/// Pushes the GPU's core temperature and, if it reports one, its memory
+/// temperature. The core temperature's thresholds are where the GPU starts to
+/// slow down and where it shuts down.
+fn push_temperatures(
+    temp_vec: &mut Vec<TempHarvest>, index: u32, name: &str, device: &Device<'_>,
+    temp_type: &TemperatureType, filter: &Option<Filter>,
+) {
+    let convert = |celsius: u32| temp_type.convert_temp_unit(celsius as f32);
+
+    if Filter::optional_should_keep(filter, name) {
+        temp_vec.push(TempHarvest {
+            name: name.to_string(),
+            sensor_id: format!("nvidia{index}/gpu"),
+            temperature: device.temperature(TemperatureSensor::Gpu).ok().map(convert),
+            unit: *temp_type,
+            high: device
+                .temperature_threshold(TemperatureThreshold::Slowdown)
+                .ok()
+                .map(convert),
+            crit: device
+                .temperature_threshold(TemperatureThreshold::Shutdown)
+                .ok()
+                .map(convert),
+            chip: Some(name.to_string()),
+            ..Default::default()
+        });
+    }
+
+    let memory_name = format!("{name} (Memory)");
+    if Filter::optional_should_keep(filter, &memory_name) {
+        if let Some(temperature) = memory_temperature(device) {
+            temp_vec.push(TempHarvest {
+                name: memory_name,
+                sensor_id: format!("nvidia{index}/memory"),
+                temperature: Some(temp_type.convert_temp_unit(temperature)),
+                unit: *temp_type,
+                chip: Some(name.to_string()),
+                ..Default::default()
+            });
+        }
+    }
+}
+
+/// Returns the speed of each NVIDIA GPU fan, named e.g. `NVIDIA GeForce RTX
+/// 3080 fan1`. NVML only reports fan speeds as a percentage of their maximum
+/// rather than in RPM, so these are [`PwmHarvest`]s.
+pub fn get_nvidia_fan_speeds(filter: &Option<Filter>) -> Vec<PwmHarvest> {
+    let mut fans = Vec::new();
+    let Ok(nvml) = NVML_DATA.get_or_init(Nvml::init) else {
+        return fans;
+    };
+    let Ok(num_gpu) = nvml.device_count() else {
+        return fans;
+    };
+
+    for i in 0..num_gpu {
+        let Ok(device) = nvml.device_by_index(i) else {
+            continue;
+        };
+        let (Ok(name), Ok(num_fans)) = (device.name(), device.num_fans()) else {
+            continue;
+        };
+
+        for fan in 0..num_fans {
+            let fan_name = format!("{name} fan{}", fan + 1);
+            if !Filter::optional_should_keep(filter, &fan_name) {
+                continue;
+            }
+
+            if let Ok(percent) = device.fan_speed(fan) {
+                fans.push(PwmHarvest {
+                    name: fan_name,
+                    sensor_id: format!("nvidia{i}/fan{}", fan + 1),
+                    percent: percent as f32,
+                });
+            }
+        }
+    }
+
+    fans
+}
+
 /// Returns the GPU data from NVIDIA cards.
 #[inline]
 pub fn get_nvidia_vecs(
@@ -53,22 +158,8 @@ pub fn get_nvidia_vecs(
                             }
                         }
 
-                        if widgets_to_harvest.use_temp
-                            && Filter::optional_should_keep(filter, &name)
-                        {
-                            if let Ok(temperature) = device.temperature(TemperatureSensor::Gpu) {
-                                let temperature = temp_type.convert_temp_unit(temperature as f32);
-
-                                temp_vec.push(TempHarvest {
-                                    name,
-                                    temperature: Some(temperature),
-                                });
-                            } else {
-                                temp_vec.push(TempHarvest {
-                                    name,
-                                    temperature: None,
-                                });
-                            }
+                        if widgets_to_harvest.use_temp {
+                            push_temperatures(&mut temp_vec, i, &name, &device, temp_type, filter);
                         }
                     }
 
@@ -1,76 +1,631 @@
 //! Data collection for temperature metrics.
 //!
-//! For Linux and macOS, this is handled by Heim.
-//! For Windows, this is handled by sysinfo.
+//! For Linux, this is handled by reading hwmon directly.
+//! For other platforms, this is handled by sysinfo.
+//!
+//! With the `lmsensors` feature, lm_sensors is used instead, and the platform
+//! backend is only used as a fallback if lm_sensors isn't available at runtime.
+//! Likewise, with the `windows` feature, LibreHardwareMonitor is used on Windows
+//! if it's running, and sysinfo otherwise.
 
 cfg_if::cfg_if! {
-    if #[cfg(feature = "lmsensors")] {
-        pub mod lm_sensors;
-        pub use self::lm_sensors::*;
-    }
-    else if #[cfg(target_os = "linux")] {
+    if #[cfg(target_os = "linux")] {
         pub mod linux;
+        #[cfg(not(feature = "lmsensors"))]
         pub use self::linux::*;
     } else if #[cfg(any(target_os = "freebsd", target_os = "macos", target_os = "windows", target_os = "android", target_os = "ios"))] {
         pub mod sysinfo;
+        #[cfg(not(feature = "lmsensors"))]
         pub use self::sysinfo::*;
     }
 }
 
-use std::str::FromStr;
+#[cfg(feature = "lmsensors")]
+pub mod lm_sensors;
+#[cfg(feature = "lmsensors")]
+pub use self::lm_sensors::*;
+
+#[cfg(all(target_os = "windows", feature = "windows"))]
+pub mod libre_hardware_monitor;
+
+mod name_rule;
+#[cfg(target_os = "linux")]
+mod power_supply;
+mod virtual_sensor;
+
+pub use self::name_rule::*;
+#[cfg(target_os = "linux")]
+pub use self::power_supply::*;
+pub use self::virtual_sensor::*;
+
+use std::{fmt, ops::RangeInclusive, str::FromStr, time::Instant};
+
+use hashbrown::HashMap;
+
+/// Which backend collected the temperatures for a tick.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TemperatureBackend {
+    /// lm_sensors, with the `lmsensors` feature.
+    LmSensors,
+    /// Reading hwmon and thermal zones directly, on Linux.
+    Hwmon,
+    /// sysinfo, on other platforms.
+    Sysinfo,
+    /// LibreHardwareMonitor over WMI, on Windows with the `windows` feature.
+    LibreHardwareMonitor,
+}
 
 #[derive(Default, Debug, Clone)]
 pub struct TempHarvest {
     pub name: String,
+    /// A stable, unique identifier for the sensor of the form
+    /// `<device>/<sensor>` (e.g. `k10temp-pci-00c3/Tctl`). Unlike `name`, this
+    /// doesn't depend on naming rules, so it should be used to key sensors.
+    pub sensor_id: String,
     pub temperature: Option<f32>,
+    /// The unit that `temperature`, `high`, and `crit` are in. Collectors convert
+    /// readings to the display unit as they go, so this is usually that.
+    pub unit: TemperatureType,
+    /// The "high" threshold for the sensor, in the same unit as `temperature`.
+    pub high: Option<f32>,
+    /// The "critical" threshold for the sensor, in the same unit as `temperature`.
+    pub crit: Option<f32>,
+    /// The raw name of the chip the sensor is on (e.g. "nct6798-isa-0290"), if
+    /// known. Unlike `name`, this isn't shortened into a friendly name.
+    pub chip: Option<String>,
+    /// The adapter the sensor is on (e.g. "PCI adapter"), if known. This can be
+    /// used to group sensors together.
+    pub adapter: Option<String>,
+    /// When the reading was collected. Readings from the same collection run
+    /// share the same timestamp.
+    pub timestamp: Option<Instant>,
+    /// Whether the sensor's alarm flag is set (e.g. an over-temperature latch),
+    /// even if the reading itself looks normal.
+    pub alarm: bool,
+    /// Whether the sensor is likely throttling, as its critical alarm (e.g.
+    /// `temp1_crit_alarm`) is set or the reading has reached `crit`.
+    pub throttling: bool,
+    /// Whether the sensor's alarms are set to beep (e.g. `temp1_beep`), if the
+    /// backend reports it.
+    pub beep_enabled: Option<bool>,
+    /// Whether this is a [`VirtualSensor`], computed from other sensors.
+    pub is_virtual: bool,
+}
+
+/// Fan PWM control data, from lm_sensors or NVML. This is separate from a fan's
+/// speed in RPM, as it's what the fan is being driven at rather than how fast
+/// it's spinning.
+#[derive(Default, Debug, Clone)]
+pub struct PwmHarvest {
+    pub name: String,
+    /// A stable id for the sensor, e.g. `<device>/<sensor>`. See
+    /// [`TempHarvest::sensor_id`].
+    pub sensor_id: String,
+    /// The duty cycle, as a percentage.
+    pub percent: f32,
+}
+
+/// How concerning a reading is relative to its sensor's thresholds, e.g. to pick
+/// a colour for it. See [`TempHarvest::severity`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum Severity {
+    /// Below the "high" threshold.
+    #[default]
+    Normal,
+    /// At or above the "high" threshold, but below the critical one.
+    Warning,
+    /// At or above the critical threshold.
+    Critical,
+}
+
+/// What temperature backends are available, to help figure out why no
+/// temperatures are showing up. See `sensor_backend_status`.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct BackendStatus {
+    /// Whether bottom was built with the `lmsensors` feature.
+    pub lmsensors_enabled: bool,
+    /// Whether the `sensors` binary was found and could be run.
+    pub sensors_available: bool,
+    /// The version of `sensors`, e.g. `3.6.0`, if it could be determined.
+    pub sensors_version: Option<String>,
+    /// How many devices were detected, or [`None`] if collection failed.
+    pub device_count: Option<usize>,
+}
+
+/// Reports the status of the temperature backends. Without the `lmsensors`
+/// feature there's nothing to probe.
+#[cfg(not(feature = "lmsensors"))]
+pub fn sensor_backend_status() -> BackendStatus {
+    BackendStatus::default()
+}
+
+impl TempHarvest {
+    /// Returns how close the sensor is to its critical threshold, as
+    /// `temperature / crit` in Celsius, clamped to `0.0..=1.0`.
+    ///
+    /// Returns [`None`] if there is no reading, or if the critical threshold is
+    /// missing or zero.
+    pub fn load_fraction(&self) -> Option<f32> {
+        let temperature = self.unit.to_celsius(self.temperature?);
+        let crit = self.crit.map(|crit| self.unit.to_celsius(crit));
+        let crit = crit.filter(|crit| *crit != 0.0)?;
+
+        Some((temperature / crit).clamp(0.0, 1.0))
+    }
+
+    /// Returns how concerning the reading is: [`Severity::Warning`] from `high`
+    /// and [`Severity::Critical`] from `crit`. If only `crit` is known, the
+    /// warning line is 90% of it in Celsius, whatever unit the harvest is in.
+    ///
+    /// This is always [`Severity::Normal`] if there is no reading, or if there are
+    /// no thresholds.
+    pub fn severity(&self) -> Severity {
+        let Some(temperature) = self.temperature else {
+            return Severity::Normal;
+        };
+        let temperature = self.unit.to_celsius(temperature);
+        let crit = self.crit.map(|crit| self.unit.to_celsius(crit));
+        let high = self
+            .high
+            .map(|high| self.unit.to_celsius(high))
+            .or(crit.map(|crit| crit * 0.9));
+
+        if crit.is_some_and(|crit| temperature >= crit) {
+            Severity::Critical
+        } else if high.is_some_and(|high| temperature >= high) {
+            Severity::Warning
+        } else {
+            Severity::Normal
+        }
+    }
+
+    /// Returns a copy with the temperature and thresholds rounded to `decimals`
+    /// decimal places. As harvested values are already in the target unit, this
+    /// should be done after conversion so that e.g. °F values round correctly.
+    pub fn round_to(&self, decimals: u8) -> Self {
+        let round = |value: Option<f32>| value.map(|value| round_to_decimals(value, decimals));
+
+        Self {
+            temperature: round(self.temperature),
+            high: round(self.high),
+            crit: round(self.crit),
+            ..self.clone()
+        }
+    }
+
+    /// Returns the reading converted from [`TempHarvest::unit`] to `unit`, or
+    /// [`None`] if there is no reading.
+    pub fn in_unit(&self, unit: TemperatureType) -> Option<f32> {
+        self.temperature
+            .map(|temperature| unit.convert_temp_unit(self.unit.to_celsius(temperature)))
+    }
+
+    /// Returns a builder for a harvest, for backends and tests that put one
+    /// together by hand. Anything not set is left as its default.
+    pub fn builder() -> TempHarvestBuilder {
+        TempHarvestBuilder::default()
+    }
+}
+
+/// Builds a [`TempHarvest`]. See [`TempHarvest::builder`].
+#[derive(Default, Debug, Clone)]
+pub struct TempHarvestBuilder {
+    harvest: TempHarvest,
+}
+
+impl TempHarvestBuilder {
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.harvest.name = name.into();
+        self
+    }
+
+    pub fn temperature(mut self, temperature: f32) -> Self {
+        self.harvest.temperature = Some(temperature);
+        self
+    }
+
+    pub fn unit(mut self, unit: TemperatureType) -> Self {
+        self.harvest.unit = unit;
+        self
+    }
+
+    pub fn adapter(mut self, adapter: impl Into<String>) -> Self {
+        self.harvest.adapter = Some(adapter.into());
+        self
+    }
+
+    pub fn high(mut self, high: f32) -> Self {
+        self.harvest.high = Some(high);
+        self
+    }
+
+    pub fn crit(mut self, crit: f32) -> Self {
+        self.harvest.crit = Some(crit);
+        self
+    }
+
+    pub fn build(self) -> TempHarvest {
+        self.harvest
+    }
+}
+
+/// Rounds `value` to `decimals` decimal places, with halfway cases rounded away
+/// from zero.
+fn round_to_decimals(value: f32, decimals: u8) -> f32 {
+    let scale = 10_f32.powi(decimals.into());
+    (value * scale).round() / scale
+}
+
+/// Smooths temperature readings with an exponential moving average, to stop them
+/// from jumping around between ticks. Each sensor is tracked separately by its
+/// [`TempHarvest::sensor_id`].
+#[derive(Debug, Clone)]
+pub struct TempSmoother {
+    /// How much weight a new reading gets, from 0.0 to 1.0. Lower values are
+    /// smoother, and 1.0 disables smoothing.
+    alpha: f32,
+    averages: HashMap<String, f32>,
+}
+
+impl TempSmoother {
+    /// Creates a smoother with the given `alpha`, which is clamped to `0.0..=1.0`.
+    pub fn new(alpha: f32) -> Self {
+        Self {
+            alpha: alpha.clamp(0.0, 1.0),
+            averages: HashMap::new(),
+        }
+    }
+
+    /// Adds a new reading and returns the smoothed temperature for its sensor.
+    /// The first reading from a sensor is returned as-is. Returns [`None`] if
+    /// the harvest has no reading, in which case the average is left alone.
+    pub fn smooth(&mut self, harvest: &TempHarvest) -> Option<f32> {
+        let temperature = harvest.temperature?;
+
+        let average = match self.averages.get_mut(&harvest.sensor_id) {
+            Some(average) => {
+                *average += self.alpha * (temperature - *average);
+                *average
+            }
+            None => {
+                self.averages.insert(harvest.sensor_id.clone(), temperature);
+                temperature
+            }
+        };
+
+        Some(average)
+    }
+}
+
+/// Tracks how quickly each sensor is heating up or cooling down, in °C per
+/// second, from successive harvests. Sensors are tracked separately by their
+/// [`TempHarvest::sensor_id`]. Readings without a timestamp are treated as
+/// having been taken when they're passed to [`TempRateTracker::update`].
+#[derive(Debug, Clone)]
+pub struct TempRateTracker {
+    /// The unit readings are in, so they can be converted back to Celsius.
+    temp_type: TemperatureType,
+    /// The last reading (in Celsius) and when it was taken, per sensor.
+    last: HashMap<String, (Instant, f32)>,
+    rates: HashMap<String, f32>,
 }
 
+impl TempRateTracker {
+    /// Creates a tracker for readings that have been converted to `temp_type`.
+    pub fn new(temp_type: TemperatureType) -> Self {
+        Self {
+            temp_type,
+            last: HashMap::new(),
+            rates: HashMap::new(),
+        }
+    }
+
+    /// Adds a new set of readings, updating the rate of any sensor that was
+    /// seen before.
+    pub fn update(&mut self, temps: &[TempHarvest]) {
+        let now = Instant::now();
+
+        for temp in temps {
+            let Some(temperature) = temp.temperature else {
+                continue;
+            };
+            let timestamp = temp.timestamp.unwrap_or(now);
+            let celsius = self.temp_type.to_celsius(temperature);
+
+            if let Some((last_timestamp, last_celsius)) = self
+                .last
+                .insert(temp.sensor_id.clone(), (timestamp, celsius))
+            {
+                let elapsed = timestamp
+                    .saturating_duration_since(last_timestamp)
+                    .as_secs_f32();
+
+                // Skip duplicate or out-of-order readings rather than dividing by zero.
+                if elapsed > 0.0 {
+                    self.rates
+                        .insert(temp.sensor_id.clone(), (celsius - last_celsius) / elapsed);
+                }
+            }
+        }
+    }
+
+    /// Returns the sensor's rate of change in °C per second, or [`None`] if
+    /// there haven't been two readings from it yet.
+    pub fn rate(&self, sensor_id: &str) -> Option<f32> {
+        self.rates.get(sensor_id).copied()
+    }
+}
+
+/// Returns the hottest sensor, skipping any without a reading. Ties go to the
+/// first sensor.
+pub fn max_temperature(temps: &[TempHarvest]) -> Option<&TempHarvest> {
+    extreme_temperature(temps, |value, best| value > best)
+}
+
+/// Returns the coldest sensor, skipping any without a reading. Ties go to the
+/// first sensor.
+pub fn min_temperature(temps: &[TempHarvest]) -> Option<&TempHarvest> {
+    extreme_temperature(temps, |value, best| value < best)
+}
+
+/// Returns the sensors with a reading above `threshold_celsius`. As readings are
+/// in the display unit, `temp_type` should be the unit they were converted to.
+pub fn hot_sensors<'a>(
+    temps: &'a [TempHarvest], threshold_celsius: f32, temp_type: &TemperatureType,
+) -> impl Iterator<Item = &'a TempHarvest> {
+    let threshold = temp_type.convert_temp_unit(threshold_celsius);
+
+    temps
+        .iter()
+        .filter(move |temp| temp.temperature.is_some_and(|value| value > threshold))
+}
+
+/// Returns the sensor whose reading is strictly `better` than all the ones
+/// before it.
+fn extreme_temperature(
+    temps: &[TempHarvest], better: impl Fn(f32, f32) -> bool,
+) -> Option<&TempHarvest> {
+    let mut extreme: Option<(&TempHarvest, f32)> = None;
+
+    for temp in temps {
+        let Some(value) = temp.temperature else {
+            continue;
+        };
+
+        if extreme.map_or(true, |(_, best)| better(value, best)) {
+            extreme = Some((temp, value));
+        }
+    }
+
+    extreme.map(|(temp, _)| temp)
+}
+
+/// The range of temperatures (in Celsius) that we consider to be real readings.
+/// Anything outside of this is likely a driver glitch (e.g. a bad i2c read) or a
+/// sentinel value.
+pub const PLAUSIBLE_CELSIUS_RANGE: RangeInclusive<f32> = -50.0..=200.0;
+
+/// The offset between Celsius and Kelvin, i.e. absolute zero in Celsius.
+///
+/// Note that 273.15 can't be represented exactly as an `f32`, and is stored as
+/// 273.149993896484375. So, e.g. 0°C formats as `273.15` with two decimals but
+/// `273.1` with one, as the value is just below the halfway point. Round with
+/// [`TempHarvest::round_to`] first if that matters.
+pub const KELVIN_OFFSET: f32 = 273.15;
+
 #[derive(Clone, Debug, Copy, PartialEq, Eq, Default)]
 pub enum TemperatureType {
     #[default]
     Celsius,
     Kelvin,
     Fahrenheit,
+    Rankine,
+    Reaumur,
+}
+
+/// How to write the unit after a temperature, e.g. `54°C`, `54 C`, or
+/// `54 celsius`.
+#[derive(Clone, Debug, Copy, PartialEq, Eq, Default)]
+pub enum UnitStyle {
+    /// The unit's symbol, directly after the value (e.g. `54°C`).
+    #[default]
+    Symbol,
+    /// The unit's letter(s) without a degree sign (e.g. `54 C`).
+    ShortLetter,
+    /// The unit's full name in lowercase (e.g. `54 celsius`).
+    LongWord,
 }
 
 impl FromStr for TemperatureType {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "fahrenheit" | "f" => Ok(TemperatureType::Fahrenheit),
-            "kelvin" | "k" => Ok(TemperatureType::Kelvin),
-            "celsius" | "c" => Ok(TemperatureType::Celsius),
+        match s.to_lowercase().as_str() {
+            "fahrenheit" | "f" | "°f" => Ok(TemperatureType::Fahrenheit),
+            "kelvin" | "k" | "°k" => Ok(TemperatureType::Kelvin),
+            "celsius" | "c" | "°c" => Ok(TemperatureType::Celsius),
+            "rankine" | "r" | "°r" => Ok(TemperatureType::Rankine),
+            "reaumur" | "re" | "°ré" => Ok(TemperatureType::Reaumur),
             _ => Err(format!(
-                "'{s}' is an invalid temperature type, use one of: [kelvin, k, celsius, c, fahrenheit, f]."
+                "'{s}' is an invalid temperature type, use one of: [kelvin, k, celsius, c, fahrenheit, f, rankine, r, reaumur, re]. Symbol forms like °c are also accepted."
             )),
         }
     }
 }
 
+impl fmt::Display for TemperatureType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.unit_symbol())
+    }
+}
+
 impl TemperatureType {
+    /// Returns the symbol for the unit. Note that Kelvin does not use a degree
+    /// sign.
+    pub fn unit_symbol(&self) -> &'static str {
+        match self {
+            TemperatureType::Celsius => "°C",
+            TemperatureType::Kelvin => "K",
+            TemperatureType::Fahrenheit => "°F",
+            TemperatureType::Rankine => "°R",
+            TemperatureType::Reaumur => "°Ré",
+        }
+    }
+
+    /// Returns the unit's letter(s) without a degree sign, as accepted when
+    /// parsing.
+    fn unit_letter(&self) -> &'static str {
+        match self {
+            TemperatureType::Celsius => "C",
+            TemperatureType::Kelvin => "K",
+            TemperatureType::Fahrenheit => "F",
+            TemperatureType::Rankine => "R",
+            TemperatureType::Reaumur => "Re",
+        }
+    }
+
+    /// Returns the unit's full name in lowercase, as accepted when parsing.
+    fn unit_name(&self) -> &'static str {
+        match self {
+            TemperatureType::Celsius => "celsius",
+            TemperatureType::Kelvin => "kelvin",
+            TemperatureType::Fahrenheit => "fahrenheit",
+            TemperatureType::Rankine => "rankine",
+            TemperatureType::Reaumur => "reaumur",
+        }
+    }
+
+    /// Formats a temperature that is already in this unit, followed by the unit
+    /// in the given style.
+    pub fn format_temp(&self, value: impl fmt::Display, style: UnitStyle) -> String {
+        match style {
+            UnitStyle::Symbol => format!("{value}{}", self.unit_symbol()),
+            UnitStyle::ShortLetter => format!("{value} {}", self.unit_letter()),
+            UnitStyle::LongWord => format!("{value} {}", self.unit_name()),
+        }
+    }
+
     /// Given a temperature in Celsius, covert it if necessary for a different
     /// unit.
     pub fn convert_temp_unit(&self, temp_celsius: f32) -> f32 {
+        self.converter()(temp_celsius)
+    }
+
+    /// Like [`TemperatureType::convert_temp_unit`], but returns [`None`] if the
+    /// temperature in Celsius isn't finite. If `valid_range` is given (e.g.
+    /// [`PLAUSIBLE_CELSIUS_RANGE`]), temperatures outside of it are dropped too,
+    /// as likely driver glitches.
+    pub fn convert_valid_temp_unit(
+        &self, temp_celsius: f32, valid_range: Option<&RangeInclusive<f32>>,
+    ) -> Option<f32> {
+        let in_range = valid_range.map_or(true, |valid_range| valid_range.contains(&temp_celsius));
+
+        (temp_celsius.is_finite() && in_range).then(|| self.convert_temp_unit(temp_celsius))
+    }
+
+    /// Given a slice of temperatures in Celsius, convert them in place if
+    /// necessary for a different unit.
+    pub fn convert_temp_unit_slice(&self, temps: &mut [f32]) {
+        if let TemperatureType::Celsius = self {
+            return;
+        }
+
+        let convert = self.converter();
+        for temp in temps {
+            *temp = convert(*temp);
+        }
+    }
+
+    /// Returns the function to convert a temperature in Celsius to this unit.
+    fn converter(&self) -> fn(f32) -> f32 {
+        fn convert_celsius(celsius: f32) -> f32 {
+            celsius
+        }
+
         fn convert_celsius_to_kelvin(celsius: f32) -> f32 {
-            celsius + 273.15
+            celsius + KELVIN_OFFSET
         }
 
         fn convert_celsius_to_fahrenheit(celsius: f32) -> f32 {
             (celsius * (9.0 / 5.0)) + 32.0
         }
 
+        fn convert_celsius_to_rankine(celsius: f32) -> f32 {
+            (celsius + KELVIN_OFFSET) * (9.0 / 5.0)
+        }
+
+        fn convert_celsius_to_reaumur(celsius: f32) -> f32 {
+            celsius * (4.0 / 5.0)
+        }
+
+        match self {
+            TemperatureType::Celsius => convert_celsius,
+            TemperatureType::Kelvin => convert_celsius_to_kelvin,
+            TemperatureType::Fahrenheit => convert_celsius_to_fahrenheit,
+            TemperatureType::Rankine => convert_celsius_to_rankine,
+            TemperatureType::Reaumur => convert_celsius_to_reaumur,
+        }
+    }
+
+    /// Given a temperature in this unit, convert it back to Celsius. This is the
+    /// inverse of [`TemperatureType::convert_temp_unit`].
+    pub fn to_celsius(&self, value: f32) -> f32 {
         match self {
-            TemperatureType::Celsius => temp_celsius,
-            TemperatureType::Kelvin => convert_celsius_to_kelvin(temp_celsius),
-            TemperatureType::Fahrenheit => convert_celsius_to_fahrenheit(temp_celsius),
+            TemperatureType::Celsius => value,
+            TemperatureType::Kelvin => value - KELVIN_OFFSET,
+            TemperatureType::Fahrenheit => (value - 32.0) * 5.0 / 9.0,
+            TemperatureType::Rankine => value * 5.0 / 9.0 - KELVIN_OFFSET,
+            TemperatureType::Reaumur => value * 5.0 / 4.0,
         }
     }
 }
 
+/// Writes `contents` to `path` under `root`, creating any missing directories,
+/// to build a fake sysfs tree for tests.
+#[cfg(all(test, target_os = "linux"))]
+fn write_sysfs_file(root: &std::path::Path, path: &str, contents: &str) {
+    let path = root.join(path);
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, contents).unwrap();
+}
+
 #[cfg(test)]
 mod test {
-    use crate::data_collection::temperature::TemperatureType;
+    use std::{
+        str::FromStr,
+        time::{Duration, Instant},
+    };
+
+    use crate::data_collection::temperature::{
+        hot_sensors, max_temperature, min_temperature, Severity, TempHarvest, TempRateTracker,
+        TempSmoother, TemperatureType, UnitStyle, KELVIN_OFFSET, PLAUSIBLE_CELSIUS_RANGE,
+    };
+
+    #[test]
+    fn temp_unit_styles() {
+        let cases = [
+            (TemperatureType::Celsius, "54°C", "54 C", "54 celsius"),
+            (TemperatureType::Kelvin, "54K", "54 K", "54 kelvin"),
+            (TemperatureType::Fahrenheit, "54°F", "54 F", "54 fahrenheit"),
+            (TemperatureType::Rankine, "54°R", "54 R", "54 rankine"),
+            (TemperatureType::Reaumur, "54°Ré", "54 Re", "54 reaumur"),
+        ];
+
+        for (temp_type, symbol, letter, word) in cases {
+            assert_eq!(temp_type.format_temp(54, UnitStyle::Symbol), symbol);
+            assert_eq!(temp_type.format_temp(54, UnitStyle::ShortLetter), letter);
+            assert_eq!(temp_type.format_temp(54, UnitStyle::LongWord), word);
+
+            // The letter and word forms can be parsed back.
+            assert_eq!(temp_type.unit_letter().parse(), Ok(temp_type));
+            assert_eq!(temp_type.unit_name().parse(), Ok(temp_type));
+        }
+
+        assert_eq!(
+            TemperatureType::Celsius.format_temp(54.5, UnitStyle::Symbol),
+            "54.5°C"
+        );
+    }
 
     #[test]
     fn temp_conversions() {
@@ -85,5 +640,414 @@ mod test {
         assert_eq!(TemperatureType::Kelvin.convert_temp_unit(TEMP), 373.15);
 
         assert_eq!(TemperatureType::Fahrenheit.convert_temp_unit(TEMP), 212.0);
+
+        assert!((TemperatureType::Rankine.convert_temp_unit(TEMP) - 671.67).abs() < 0.01);
+
+        assert_eq!(TemperatureType::Reaumur.convert_temp_unit(TEMP), 80.0);
+    }
+
+    #[test]
+    fn temp_kelvin_precision() {
+        let kelvin = TemperatureType::Kelvin.convert_temp_unit(0.0);
+
+        // 0°C is exactly the offset, which as an f32 is just below 273.15.
+        assert_eq!(kelvin, KELVIN_OFFSET);
+        assert!(f64::from(kelvin) < 273.15);
+        assert_eq!(TemperatureType::Kelvin.to_celsius(kelvin), 0.0);
+
+        assert_eq!(format!("{kelvin}"), "273.15");
+        assert_eq!(format!("{kelvin:.2}"), "273.15");
+        assert_eq!(format!("{kelvin:.1}"), "273.1");
+        assert_eq!(format!("{kelvin:.0}"), "273");
+    }
+
+    #[test]
+    fn temp_valid_conversions() {
+        let range = Some(&PLAUSIBLE_CELSIUS_RANGE);
+
+        assert_eq!(
+            TemperatureType::Fahrenheit.convert_valid_temp_unit(100.0, range),
+            Some(212.0)
+        );
+        assert_eq!(
+            TemperatureType::Fahrenheit.convert_valid_temp_unit(65535.0, range),
+            None
+        );
+        assert_eq!(
+            TemperatureType::Kelvin.convert_valid_temp_unit(-273.15, range),
+            None
+        );
+        assert_eq!(
+            TemperatureType::Celsius.convert_valid_temp_unit(f32::NAN, range),
+            None
+        );
+        assert_eq!(
+            TemperatureType::Celsius.convert_valid_temp_unit(65535.0, Some(&(0.0..=f32::MAX))),
+            Some(65535.0)
+        );
+
+        // Without a range, only non-finite readings are dropped, so e.g. a real
+        // -62°C reading from a cold-storage probe is kept.
+        assert_eq!(
+            TemperatureType::Celsius.convert_valid_temp_unit(-62.0, None),
+            Some(-62.0)
+        );
+        assert_eq!(
+            TemperatureType::Celsius.convert_valid_temp_unit(f32::NAN, None),
+            None
+        );
+    }
+
+    #[test]
+    fn temp_slice_conversions() {
+        const TEMPS: [f32; 5] = [-40.0, 0.0, 36.6, 54.5, 100.0];
+
+        for temp_type in [
+            TemperatureType::Celsius,
+            TemperatureType::Kelvin,
+            TemperatureType::Fahrenheit,
+            TemperatureType::Rankine,
+            TemperatureType::Reaumur,
+        ] {
+            let mut temps = TEMPS;
+            temp_type.convert_temp_unit_slice(&mut temps);
+
+            for (converted, original) in temps.into_iter().zip(TEMPS) {
+                assert_eq!(converted, temp_type.convert_temp_unit(original));
+            }
+        }
+    }
+
+    #[test]
+    fn temp_min_max() {
+        let temp = |name: &str, temperature: Option<f32>| TempHarvest {
+            name: name.to_string(),
+            temperature,
+            ..Default::default()
+        };
+
+        assert!(max_temperature(&[]).is_none());
+        assert!(min_temperature(&[temp("a", None), temp("b", None)]).is_none());
+
+        let temps = [
+            temp("a", None),
+            temp("b", Some(40.0)),
+            temp("c", Some(70.0)),
+            temp("d", Some(30.0)),
+            temp("e", Some(70.0)),
+            temp("f", Some(30.0)),
+        ];
+        assert_eq!(max_temperature(&temps).unwrap().name, "c");
+        assert_eq!(min_temperature(&temps).unwrap().name, "d");
+    }
+
+    #[test]
+    fn temp_hot_sensors() {
+        let temp = |name: &str, temperature: Option<f32>| TempHarvest {
+            name: name.to_string(),
+            temperature,
+            ..Default::default()
+        };
+
+        let temps = [
+            temp("a", Some(60.0)),
+            temp("b", None),
+            temp("c", Some(85.0)),
+            temp("d", Some(80.0)),
+        ];
+        let names = hot_sensors(&temps, 80.0, &TemperatureType::Celsius)
+            .map(|temp| temp.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["c"]);
+
+        // 80°C is 176°F.
+        let temps = [
+            temp("a", Some(170.0)),
+            temp("b", None),
+            temp("c", Some(180.0)),
+        ];
+        let names = hot_sensors(&temps, 80.0, &TemperatureType::Fahrenheit)
+            .map(|temp| temp.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["c"]);
+    }
+
+    #[test]
+    fn temp_load_fraction() {
+        let temp = |temperature: Option<f32>, crit: Option<f32>| TempHarvest {
+            temperature,
+            crit,
+            ..Default::default()
+        };
+
+        assert_eq!(temp(Some(50.0), Some(100.0)).load_fraction(), Some(0.5));
+        assert_eq!(temp(Some(105.0), Some(100.0)).load_fraction(), Some(1.0));
+        assert_eq!(temp(Some(-5.0), Some(100.0)).load_fraction(), Some(0.0));
+        assert_eq!(temp(Some(50.0), Some(0.0)).load_fraction(), None);
+        assert_eq!(temp(Some(50.0), None).load_fraction(), None);
+        assert_eq!(temp(None, Some(100.0)).load_fraction(), None);
+
+        // The ratio is taken in Celsius, so 47°C against a 100°C crit is 0.47
+        // whatever unit the harvest is in.
+        let kelvin = TempHarvest {
+            temperature: Some(TemperatureType::Kelvin.convert_temp_unit(47.0)),
+            crit: Some(TemperatureType::Kelvin.convert_temp_unit(100.0)),
+            unit: TemperatureType::Kelvin,
+            ..Default::default()
+        };
+        assert!(kelvin
+            .load_fraction()
+            .is_some_and(|fraction| (fraction - 0.47).abs() < 0.0001));
+    }
+
+    #[test]
+    fn temp_rounding() {
+        let temp = |temp_type: TemperatureType| TempHarvest {
+            temperature: Some(temp_type.convert_temp_unit(54.125)),
+            crit: Some(temp_type.convert_temp_unit(100.0)),
+            ..Default::default()
+        };
+
+        let celsius = temp(TemperatureType::Celsius);
+        assert_eq!(celsius.round_to(0).temperature, Some(54.0));
+        assert_eq!(celsius.round_to(1).temperature, Some(54.1));
+        assert_eq!(celsius.round_to(1).crit, Some(100.0));
+
+        // 54.125°C is 129.425°F; rounding to whole degrees before converting would
+        // give 129.2°F instead.
+        let fahrenheit = temp(TemperatureType::Fahrenheit);
+        assert_eq!(fahrenheit.round_to(0).temperature, Some(129.0));
+        assert_eq!(fahrenheit.round_to(1).temperature, Some(129.4));
+        assert_eq!(fahrenheit.round_to(1).crit, Some(212.0));
+
+        assert_eq!(TempHarvest::default().round_to(1).temperature, None);
+    }
+
+    #[test]
+    fn temp_smoothing() {
+        let temp = |sensor_id: &str, temperature: Option<f32>| TempHarvest {
+            sensor_id: sensor_id.to_string(),
+            temperature,
+            ..Default::default()
+        };
+
+        let mut smoother = TempSmoother::new(0.5);
+
+        assert_eq!(smoother.smooth(&temp("a", Some(40.0))), Some(40.0));
+        assert_eq!(smoother.smooth(&temp("a", Some(60.0))), Some(50.0));
+        assert_eq!(smoother.smooth(&temp("a", None)), None);
+        assert_eq!(smoother.smooth(&temp("a", Some(70.0))), Some(60.0));
+
+        // Unknown sensors start fresh from their first reading.
+        assert_eq!(smoother.smooth(&temp("b", Some(30.0))), Some(30.0));
+        assert_eq!(smoother.smooth(&temp("a", Some(60.0))), Some(60.0));
+    }
+
+    #[test]
+    fn temp_harvest_builder() {
+        let harvest = TempHarvest::builder()
+            .name("Tctl")
+            .temperature(45.0)
+            .adapter("PCI adapter")
+            .crit(95.0)
+            .build();
+
+        assert_eq!(harvest.name, "Tctl");
+        assert_eq!(harvest.temperature, Some(45.0));
+        assert_eq!(harvest.adapter.as_deref(), Some("PCI adapter"));
+        assert_eq!(harvest.high, None);
+        assert_eq!(harvest.crit, Some(95.0));
+        assert_eq!(harvest.chip, None);
+        assert_eq!(harvest.timestamp, None);
+
+        let harvest = TempHarvest::builder().build();
+        assert_eq!(harvest.name, "");
+        assert_eq!(harvest.temperature, None);
+        assert_eq!(harvest.adapter, None);
+        assert_eq!(harvest.crit, None);
+    }
+
+    #[test]
+    fn temp_severity() {
+        let temp = |temperature: f32, high: Option<f32>, crit: Option<f32>| TempHarvest {
+            temperature: Some(temperature),
+            high,
+            crit,
+            ..Default::default()
+        };
+
+        // Both thresholds.
+        assert_eq!(
+            temp(69.9, Some(70.0), Some(90.0)).severity(),
+            Severity::Normal
+        );
+        assert_eq!(
+            temp(70.0, Some(70.0), Some(90.0)).severity(),
+            Severity::Warning
+        );
+        assert_eq!(
+            temp(89.9, Some(70.0), Some(90.0)).severity(),
+            Severity::Warning
+        );
+        assert_eq!(
+            temp(90.0, Some(70.0), Some(90.0)).severity(),
+            Severity::Critical
+        );
+
+        // Only crit, so the warning line is 90% of it.
+        assert_eq!(temp(89.0, None, Some(100.0)).severity(), Severity::Normal);
+        assert_eq!(temp(90.0, None, Some(100.0)).severity(), Severity::Warning);
+        assert_eq!(
+            temp(100.0, None, Some(100.0)).severity(),
+            Severity::Critical
+        );
+
+        // Only high.
+        assert_eq!(temp(69.0, Some(70.0), None).severity(), Severity::Normal);
+        assert_eq!(temp(150.0, Some(70.0), None).severity(), Severity::Warning);
+
+        // The warning line is 90°C for a 100°C crit in any unit, i.e. 363.15 K or
+        // 194°F.
+        for temp_type in [TemperatureType::Kelvin, TemperatureType::Fahrenheit] {
+            let temp = |celsius: f32| TempHarvest {
+                temperature: Some(temp_type.convert_temp_unit(celsius)),
+                unit: temp_type,
+                crit: Some(temp_type.convert_temp_unit(100.0)),
+                ..Default::default()
+            };
+
+            assert_eq!(temp(89.0).severity(), Severity::Normal, "{temp_type:?}");
+            assert_eq!(temp(91.0).severity(), Severity::Warning, "{temp_type:?}");
+            assert_eq!(temp(100.0).severity(), Severity::Critical, "{temp_type:?}");
+        }
+
+        // No thresholds or no reading.
+        assert_eq!(temp(150.0, None, None).severity(), Severity::Normal);
+        assert_eq!(
+            TempHarvest {
+                crit: Some(90.0),
+                ..Default::default()
+            }
+            .severity(),
+            Severity::Normal
+        );
+    }
+
+    #[test]
+    fn temp_in_unit() {
+        let harvest = TempHarvest::builder().temperature(100.0).build();
+        assert_eq!(harvest.in_unit(TemperatureType::Celsius), Some(100.0));
+        assert_eq!(harvest.in_unit(TemperatureType::Fahrenheit), Some(212.0));
+        assert_eq!(harvest.in_unit(TemperatureType::Kelvin), Some(373.15));
+
+        // A reading that was collected in Fahrenheit isn't converted twice.
+        let harvest = TempHarvest::builder()
+            .temperature(212.0)
+            .unit(TemperatureType::Fahrenheit)
+            .build();
+        assert_eq!(harvest.in_unit(TemperatureType::Fahrenheit), Some(212.0));
+        assert_eq!(harvest.in_unit(TemperatureType::Celsius), Some(100.0));
+
+        let harvest = TempHarvest::builder().name("temp1").build();
+        assert_eq!(harvest.in_unit(TemperatureType::Fahrenheit), None);
+    }
+
+    #[test]
+    fn temp_rate_tracking() {
+        let start = Instant::now();
+        let temp = |sensor_id: &str, temperature: f32, secs: u64| TempHarvest {
+            sensor_id: sensor_id.to_string(),
+            temperature: Some(temperature),
+            timestamp: Some(start + Duration::from_secs(secs)),
+            ..Default::default()
+        };
+
+        let mut tracker = TempRateTracker::new(TemperatureType::Celsius);
+
+        tracker.update(&[temp("a", 40.0, 0)]);
+        assert_eq!(tracker.rate("a"), None);
+
+        tracker.update(&[temp("a", 50.0, 4), temp("b", 30.0, 4)]);
+        assert_eq!(tracker.rate("a"), Some(2.5));
+        assert_eq!(tracker.rate("b"), None);
+
+        tracker.update(&[temp("a", 48.0, 6)]);
+        assert_eq!(tracker.rate("a"), Some(-1.0));
+
+        // Rates are in Celsius, whatever unit the readings are in.
+        let mut tracker = TempRateTracker::new(TemperatureType::Fahrenheit);
+        tracker.update(&[temp("a", 104.0, 0)]);
+        tracker.update(&[temp("a", 122.0, 5)]);
+        assert_eq!(tracker.rate("a"), Some(2.0));
+
+        // Readings without a timestamp are timed when they're added.
+        let untimed = |temperature: f32| TempHarvest {
+            sensor_id: "a".to_string(),
+            temperature: Some(temperature),
+            ..Default::default()
+        };
+        let mut tracker = TempRateTracker::new(TemperatureType::Celsius);
+        tracker.update(&[untimed(40.0)]);
+        std::thread::sleep(Duration::from_millis(10));
+        tracker.update(&[untimed(50.0)]);
+        assert!(tracker.rate("a").is_some_and(|rate| rate > 0.0));
+    }
+
+    #[test]
+    fn temp_from_str() {
+        assert_eq!(
+            TemperatureType::from_str("°C"),
+            Ok(TemperatureType::Celsius)
+        );
+        assert_eq!(
+            TemperatureType::from_str("°f"),
+            Ok(TemperatureType::Fahrenheit)
+        );
+        assert_eq!(TemperatureType::from_str("°K"), Ok(TemperatureType::Kelvin));
+        assert_eq!(
+            TemperatureType::from_str("Fahrenheit"),
+            Ok(TemperatureType::Fahrenheit)
+        );
+        assert_eq!(TemperatureType::from_str("c"), Ok(TemperatureType::Celsius));
+        assert_eq!(
+            TemperatureType::from_str("re"),
+            Ok(TemperatureType::Reaumur)
+        );
+        assert_eq!(
+            TemperatureType::from_str("°Ré"),
+            Ok(TemperatureType::Reaumur)
+        );
+        assert!(TemperatureType::from_str("°x").is_err());
+    }
+
+    #[test]
+    fn temp_unit_symbols() {
+        assert_eq!(TemperatureType::Celsius.to_string(), "°C");
+        assert_eq!(TemperatureType::Kelvin.to_string(), "K");
+        assert_eq!(TemperatureType::Fahrenheit.to_string(), "°F");
+        assert_eq!(TemperatureType::Rankine.to_string(), "°R");
+        assert_eq!(TemperatureType::Reaumur.to_string(), "°Ré");
+    }
+
+    #[test]
+    fn temp_round_trips() {
+        assert_eq!(TemperatureType::Fahrenheit.to_celsius(212.0), 100.0);
+
+        for temp_type in [
+            TemperatureType::Celsius,
+            TemperatureType::Kelvin,
+            TemperatureType::Fahrenheit,
+            TemperatureType::Rankine,
+            TemperatureType::Reaumur,
+        ] {
+            for temp in [-40.0, 0.0, 36.6, 100.0, 1000.0] {
+                let round_trip = temp_type.to_celsius(temp_type.convert_temp_unit(temp));
+
+                assert!(
+                    (round_trip - temp).abs() < 0.001,
+                    "{temp_type:?} round trip of {temp} gave {round_trip}"
+                );
+            }
+        }
     }
 }
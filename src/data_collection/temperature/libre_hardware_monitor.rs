@@ -0,0 +1,158 @@
+//! Gets temperature data from LibreHardwareMonitor (or Open Hardware Monitor)
+//! over WMI, which reports far more sensors than sysinfo does on Windows. This
+//! only works while the app is running.
+
+use std::ops::RangeInclusive;
+
+use anyhow::Result;
+use hashbrown::HashMap;
+use serde::Deserialize;
+use wmi::{COMLibrary, WMIConnection};
+
+use super::{TempHarvest, TemperatureType};
+use crate::app::filter::Filter;
+
+/// The WMI namespaces to try, in order. Open Hardware Monitor uses the same
+/// classes as its successor.
+const NAMESPACES: &[&str] = &["root\\LibreHardwareMonitor", "root\\OpenHardwareMonitor"];
+
+#[derive(Deserialize)]
+#[serde(rename = "Hardware", rename_all = "PascalCase")]
+struct Hardware {
+    identifier: String,
+    name: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "Sensor", rename_all = "PascalCase")]
+struct Sensor {
+    /// e.g. `/amdcpu/0/temperature/2`.
+    identifier: String,
+    name: String,
+    /// e.g. `Temperature` or `Fan`.
+    sensor_type: String,
+    /// The reading; temperatures are always in Celsius.
+    value: f32,
+    /// The identifier of the hardware the sensor is on, e.g. `/amdcpu/0`.
+    parent: String,
+}
+
+/// Builds harvests from the temperature sensors, naming each after the
+/// hardware it's on (e.g. `AMD Ryzen 7 5800X: Core (Tctl/Tdie)`).
+fn temperature_harvests(
+    hardware: &[Hardware], sensors: Vec<Sensor>, temp_type: &TemperatureType,
+    filter: &Option<Filter>, valid_range: Option<&RangeInclusive<f32>>,
+) -> Vec<TempHarvest> {
+    let hardware_names: HashMap<&str, &str> = hardware
+        .iter()
+        .map(|hardware| (hardware.identifier.as_str(), hardware.name.as_str()))
+        .collect();
+
+    sensors
+        .into_iter()
+        .filter(|sensor| sensor.sensor_type == "Temperature")
+        .filter_map(|sensor| {
+            let hardware_name = hardware_names.get(sensor.parent.as_str());
+            let name = match hardware_name {
+                Some(hardware_name) => format!("{hardware_name}: {}", sensor.name),
+                None => sensor.name,
+            };
+
+            if !Filter::optional_should_keep(filter, &name) {
+                return None;
+            }
+
+            Some(TempHarvest {
+                name,
+                sensor_id: format!("lhm{}", sensor.identifier),
+                temperature: temp_type.convert_valid_temp_unit(sensor.value, valid_range),
+                unit: *temp_type,
+                chip: hardware_name.map(|name| name.to_string()),
+                ..Default::default()
+            })
+        })
+        .collect()
+}
+
+/// Gets temperatures from LibreHardwareMonitor. This is [`None`] if neither it
+/// nor Open Hardware Monitor is running, so that another backend can be used.
+///
+/// If `valid_range` is given, readings (in Celsius) outside of it are dropped.
+pub fn get_temperature_data(
+    temp_type: &TemperatureType, filter: &Option<Filter>, valid_range: Option<&RangeInclusive<f32>>,
+) -> Result<Option<Vec<TempHarvest>>> {
+    let com = COMLibrary::new()?;
+
+    for namespace in NAMESPACES {
+        // The namespace only exists while the app is running.
+        let Ok(connection) = WMIConnection::with_namespace_path(namespace, com) else {
+            continue;
+        };
+
+        let hardware: Vec<Hardware> = connection.query()?;
+        let sensors: Vec<Sensor> = connection.query()?;
+
+        return Ok(Some(temperature_harvests(
+            &hardware,
+            sensors,
+            temp_type,
+            filter,
+            valid_range,
+        )));
+    }
+
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_temperature_harvests() {
+        let hardware = [Hardware {
+            identifier: "/amdcpu/0".to_string(),
+            name: "AMD Ryzen 7 5800X".to_string(),
+        }];
+        let sensor =
+            |identifier: &str, name: &str, sensor_type: &str, value, parent: &str| Sensor {
+                identifier: identifier.to_string(),
+                name: name.to_string(),
+                sensor_type: sensor_type.to_string(),
+                value,
+                parent: parent.to_string(),
+            };
+        let sensors = vec![
+            sensor(
+                "/amdcpu/0/temperature/2",
+                "Core (Tctl/Tdie)",
+                "Temperature",
+                45.0,
+                "/amdcpu/0",
+            ),
+            sensor("/amdcpu/0/load/0", "CPU Total", "Load", 12.0, "/amdcpu/0"),
+            sensor(
+                "/nvme/0/temperature/0",
+                "Temperature",
+                "Temperature",
+                38.0,
+                "/nvme/0",
+            ),
+        ];
+
+        let temperatures = temperature_harvests(
+            &hardware,
+            sensors,
+            &TemperatureType::Fahrenheit,
+            &None,
+            None,
+        );
+        assert_eq!(temperatures.len(), 2);
+        assert_eq!(temperatures[0].name, "AMD Ryzen 7 5800X: Core (Tctl/Tdie)");
+        assert_eq!(temperatures[0].sensor_id, "lhm/amdcpu/0/temperature/2");
+        assert_eq!(temperatures[0].temperature, Some(113.0));
+        assert_eq!(temperatures[0].chip.as_deref(), Some("AMD Ryzen 7 5800X"));
+        assert_eq!(temperatures[1].name, "Temperature");
+        assert_eq!(temperatures[1].chip, None);
+    }
+}
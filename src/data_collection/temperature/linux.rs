@@ -2,6 +2,7 @@
 
 use std::{
     fs,
+    ops::RangeInclusive,
     path::{Path, PathBuf},
 };
 
@@ -223,7 +224,9 @@ fn is_device_awake(path: &Path) -> bool {
 /// the device is already in ACPI D0. This has the notable issue that
 /// once this happens, the device will be *kept* on through the sensor
 /// reading, and not be able to re-enter ACPI D3cold.
-fn hwmon_temperatures(temp_type: &TemperatureType, filter: &Option<Filter>) -> HwmonResults {
+fn hwmon_temperatures(
+    temp_type: &TemperatureType, filter: &Option<Filter>, valid_range: Option<&RangeInclusive<f32>>,
+) -> HwmonResults {
     let mut temperatures: Vec<TempHarvest> = vec![];
     let mut seen_names: HashMap<String, u32> = HashMap::new();
 
@@ -243,12 +246,19 @@ fn hwmon_temperatures(temp_type: &TemperatureType, filter: &Option<Filter>) -> H
     // also allow easy cancellation/timeouts.
     for file_path in dirs {
         let sensor_name = read_to_string_lossy(file_path.join("name"));
+        let hwmon_id = file_path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
 
         if !is_device_awake(&file_path) {
             let name = finalize_name(None, None, &sensor_name, &mut seen_names);
             temperatures.push(TempHarvest {
                 name,
+                sensor_id: hwmon_id,
                 temperature: None,
+                unit: *temp_type,
+                ..Default::default()
             });
 
             continue;
@@ -257,16 +267,16 @@ fn hwmon_temperatures(temp_type: &TemperatureType, filter: &Option<Filter>) -> H
         if let Ok(dir_entries) = file_path.read_dir() {
             // Enumerate the devices temperature sensors
             for file in dir_entries.flatten() {
-                let name = file.file_name();
-                let name = name.to_string_lossy();
+                let file_name = file.file_name();
+                let file_name = file_name.to_string_lossy();
 
                 // We only want temperature sensors, skip others early
-                if !(name.starts_with("temp") && name.ends_with("input")) {
+                if !(file_name.starts_with("temp") && file_name.ends_with("input")) {
                     continue;
                 }
 
                 let temp_path = file.path();
-                let sensor_label_path = file_path.join(name.replace("input", "label"));
+                let sensor_label_path = file_path.join(file_name.replace("input", "label"));
                 let sensor_label = read_to_string_lossy(sensor_label_path);
 
                 // Do some messing around to get a more sensible name for sensors:
@@ -331,7 +341,14 @@ fn hwmon_temperatures(temp_type: &TemperatureType, filter: &Option<Filter>) -> H
                     if let Ok(temp_celsius) = parse_temp(&temp_path) {
                         temperatures.push(TempHarvest {
                             name,
-                            temperature: Some(temp_type.convert_temp_unit(temp_celsius)),
+                            sensor_id: format!(
+                                "{hwmon_id}/{}",
+                                file_name.trim_end_matches("_input")
+                            ),
+                            temperature: temp_type
+                                .convert_valid_temp_unit(temp_celsius, valid_range),
+                            unit: *temp_type,
+                            ..Default::default()
                         });
                     }
                 }
@@ -353,6 +370,7 @@ fn hwmon_temperatures(temp_type: &TemperatureType, filter: &Option<Filter>) -> H
 /// for more details.
 fn add_thermal_zone_temperatures(
     temperatures: &mut Vec<TempHarvest>, temp_type: &TemperatureType, filter: &Option<Filter>,
+    valid_range: Option<&RangeInclusive<f32>>,
 ) {
     let path = Path::new("/sys/class/thermal");
     let Ok(read_dir) = path.read_dir() else {
@@ -384,7 +402,11 @@ fn add_thermal_zone_temperatures(
 
                         temperatures.push(TempHarvest {
                             name,
-                            temperature: Some(temp_type.convert_temp_unit(temp_celsius)),
+                            sensor_id: format!("thermal/{}", entry.file_name().to_string_lossy()),
+                            temperature: temp_type
+                                .convert_valid_temp_unit(temp_celsius, valid_range),
+                            unit: *temp_type,
+                            ..Default::default()
                         });
                     }
                 }
@@ -397,10 +419,24 @@ fn add_thermal_zone_temperatures(
 pub fn get_temperature_data(
     temp_type: &TemperatureType, filter: &Option<Filter>,
 ) -> Result<Option<Vec<TempHarvest>>> {
-    let mut results = hwmon_temperatures(temp_type, filter);
+    collect_temperatures(temp_type, filter, None)
+}
+
+/// Like [`get_temperature_data`], but drops readings (in Celsius) outside of
+/// `valid_range`, e.g. [`PLAUSIBLE_CELSIUS_RANGE`](super::PLAUSIBLE_CELSIUS_RANGE).
+pub fn get_valid_temperature_data(
+    temp_type: &TemperatureType, filter: &Option<Filter>, valid_range: &RangeInclusive<f32>,
+) -> Result<Option<Vec<TempHarvest>>> {
+    collect_temperatures(temp_type, filter, Some(valid_range))
+}
+
+fn collect_temperatures(
+    temp_type: &TemperatureType, filter: &Option<Filter>, valid_range: Option<&RangeInclusive<f32>>,
+) -> Result<Option<Vec<TempHarvest>>> {
+    let mut results = hwmon_temperatures(temp_type, filter, valid_range);
 
     if results.num_hwmon == 0 {
-        add_thermal_zone_temperatures(&mut results.temperatures, temp_type, filter);
+        add_thermal_zone_temperatures(&mut results.temperatures, temp_type, filter, valid_range);
     }
 
     Ok(Some(results.temperatures))
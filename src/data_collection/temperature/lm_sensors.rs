@@ -1,9 +1,206 @@
-use anyhow::Result;
-use std::process::Command;
+mod cache;
+mod export;
+#[cfg(unix)]
+mod libsensors;
+#[cfg(feature = "async")]
+mod nonblocking;
+#[cfg(target_os = "linux")]
+mod sysfs;
+
+#[cfg(feature = "async")]
+pub use self::nonblocking::*;
+pub use self::{cache::*, export::*};
+
+use anyhow::{anyhow, bail, Context, Result};
+use hashbrown::HashMap;
+use serde_json::{Map, Value};
+use std::{
+    borrow::Cow,
+    fmt::Write,
+    io::{self, Read},
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+    process::{Command, Output, Stdio},
+    sync::{Mutex, OnceLock},
+    thread,
+    time::{Duration, Instant},
+};
 
 use crate::app::filter::Filter;
 
-use super::{TempHarvest, TemperatureType};
+use super::{BackendStatus, NameRule, PwmHarvest, TempHarvest, TemperatureType};
+
+/// Fan sensor data harvested from lm_sensors.
+#[derive(Default, Debug, Clone)]
+pub struct FanHarvest {
+    pub name: String,
+    /// A stable id for the sensor, as `<device>/<sensor>`. See
+    /// [`TempHarvest::sensor_id`].
+    pub sensor_id: String,
+    /// The fan's speed, or zero if it isn't [`present`](Self::present).
+    pub rpm: f32,
+    /// Whether a fan is actually connected to the header. This is false if the
+    /// driver flags a fault (`fanN_fault`) or the reading is missing, and lets a
+    /// stopped fan (e.g. a semi-passive one at idle) be told apart from an
+    /// unpopulated header. Assumed true if there's no fault information.
+    pub present: bool,
+    /// Whether the sensor's alarm flag (e.g. `fan1_alarm`) is set.
+    pub alarm: bool,
+    /// Whether the fan's alarms are set to beep (`fanN_beep`), if reported.
+    pub beep_enabled: Option<bool>,
+    /// The fan's clock divisor (`fanN_div`), if reported. See
+    /// `FAN_DIVISOR_DRIVERS` for when this is used to scale `rpm`.
+    pub divisor: Option<f32>,
+    /// The fan's minimum speed (`fanN_min`), if reported.
+    pub min: Option<f32>,
+    /// The fan's maximum speed (`fanN_max`), if reported.
+    pub max: Option<f32>,
+}
+
+impl FanHarvest {
+    /// Returns how fast the fan is spinning within its range, from 0.0 at `min`
+    /// (or zero if there's no minimum) to 1.0 at `max`, clamped to that range.
+    ///
+    /// Returns [`None`] if the fan isn't present, or if the maximum is unknown or
+    /// not above the minimum.
+    pub fn speed_fraction(&self) -> Option<f32> {
+        if !self.present {
+            return None;
+        }
+
+        let min = self.min.unwrap_or(0.0);
+        let max = self.max.filter(|max| *max > min)?;
+
+        Some(((self.rpm - min) / (max - min)).clamp(0.0, 1.0))
+    }
+
+    /// Returns a rough estimate of how loud the fan is from its speed. See
+    /// [`NoiseThresholds`].
+    pub fn noise_tier(&self, thresholds: &NoiseThresholds) -> NoiseTier {
+        thresholds.tier(self.rpm)
+    }
+}
+
+/// A rough estimate of how loud a fan is, for dashboards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum NoiseTier {
+    Quiet,
+    Normal,
+    Loud,
+}
+
+/// The fan speeds (in RPM) at which fans are considered louder. The defaults
+/// are a rough guide, as how loud a fan is at a given speed depends a lot on
+/// its size.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NoiseThresholds {
+    /// Fans below this are [`NoiseTier::Quiet`]. Defaults to 600 RPM.
+    pub quiet_below: f32,
+    /// Fans below this (and not quiet) are [`NoiseTier::Normal`], and ones at
+    /// or above it are [`NoiseTier::Loud`]. Defaults to 1500 RPM.
+    pub normal_below: f32,
+}
+
+impl Default for NoiseThresholds {
+    fn default() -> Self {
+        Self {
+            quiet_below: 600.0,
+            normal_below: 1500.0,
+        }
+    }
+}
+
+impl NoiseThresholds {
+    /// Returns the noise tier for a fan spinning at `rpm`.
+    pub fn tier(&self, rpm: f32) -> NoiseTier {
+        if rpm < self.quiet_below {
+            NoiseTier::Quiet
+        } else if rpm < self.normal_below {
+            NoiseTier::Normal
+        } else {
+            NoiseTier::Loud
+        }
+    }
+}
+
+/// Returns the noise tier for a fan spinning at `rpm`, with the default
+/// [`NoiseThresholds`].
+pub fn noise_tier(rpm: f32) -> NoiseTier {
+    NoiseThresholds::default().tier(rpm)
+}
+
+/// Voltage sensor data harvested from lm_sensors.
+#[derive(Default, Debug, Clone)]
+pub struct VoltageHarvest {
+    pub name: String,
+    /// A stable id for the sensor, as `<device>/<sensor>`. See
+    /// [`TempHarvest::sensor_id`].
+    pub sensor_id: String,
+    /// The reading, or [`None`] if the sensor has no `_input` value.
+    pub volts: Option<f32>,
+    /// Whether the sensor's alarm flag (e.g. `in0_alarm`) is set.
+    pub alarm: bool,
+}
+
+/// Power sensor data harvested from lm_sensors.
+#[derive(Default, Debug, Clone)]
+pub struct PowerHarvest {
+    pub name: String,
+    /// A stable id for the sensor, as `<device>/<sensor>`. See
+    /// [`TempHarvest::sensor_id`].
+    pub sensor_id: String,
+    /// The reading, or [`None`] if the sensor has no `_input` value.
+    pub watts: Option<f32>,
+    /// Whether the sensor's alarm flag (e.g. `power1_alarm`) is set.
+    pub alarm: bool,
+}
+
+/// Current sensor data harvested from lm_sensors.
+#[derive(Default, Debug, Clone)]
+pub struct CurrentHarvest {
+    pub name: String,
+    /// A stable id for the sensor, as `<device>/<sensor>`. See
+    /// [`TempHarvest::sensor_id`].
+    pub sensor_id: String,
+    pub amps: f32,
+    /// Whether the sensor's alarm flag (e.g. `curr1_alarm`) is set.
+    pub alarm: bool,
+}
+
+/// Humidity sensor data harvested from lm_sensors.
+#[derive(Default, Debug, Clone)]
+pub struct HumidityHarvest {
+    pub name: String,
+    /// A stable id for the sensor, as `<device>/<sensor>`. See
+    /// [`TempHarvest::sensor_id`].
+    pub sensor_id: String,
+    /// Relative humidity, as a percentage.
+    pub percent: f32,
+}
+
+/// Chassis intrusion detection data harvested from lm_sensors.
+#[derive(Default, Debug, Clone)]
+pub struct IntrusionHarvest {
+    pub name: String,
+    /// A stable id for the sensor, as `<device>/<sensor>`. See
+    /// [`TempHarvest::sensor_id`].
+    pub sensor_id: String,
+    /// Whether the chassis intrusion alarm has been tripped.
+    pub triggered: bool,
+}
+
+/// The names of a sensor, for building filters that match it.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct SensorName {
+    /// The type of sensor, e.g. `temperature` or `fan`.
+    pub sensor_type: &'static str,
+    /// The raw name of the device the sensor is on, e.g. `k10temp-pci-00c3`.
+    pub device: String,
+    /// The sensor's name as lm_sensors reports it, e.g. `Tctl`.
+    pub raw_name: String,
+    /// The name bottom shows for the sensor, e.g. `CPU: Tctl`.
+    pub friendly_name: String,
+}
 
 /// Returned devices from grabbing lm_sensors data
 /// name/adaptor/sensors
@@ -17,104 +214,592 @@ struct LmSensorsDevice {
 /// values/names
 struct LmSensorsSensor {
     name: String,
-    value: f32,
+    /// The prefix of the sensor's subfields, e.g. `temp1`.
+    prefix: String,
+    /// The reading, or [`None`] if it was unreadable or invalid.
+    value: Option<f32>,
     sensor_type: LmSensorsSensorType,
+    high: Option<f32>,
+    crit: Option<f32>,
+    /// The minimum threshold (e.g. `fan1_min`).
+    min: Option<f32>,
+    /// Whether any of the sensor's alarm subfields are set.
+    alarm: bool,
+    /// Whether the sensor's critical alarm subfield (e.g. `temp1_crit_alarm`) is
+    /// set.
+    crit_alarm: bool,
+    /// Whether the sensor's fault subfield (e.g. `fan1_fault`) is set.
+    fault: bool,
+    /// Whether the sensor's alarms beep (e.g. `temp1_beep`), if reported.
+    beep: Option<bool>,
+    /// The clock divisor for fans (`fanN_div`).
+    divisor: Option<f32>,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 enum LmSensorsSensorType {
     Temp,
     Fan,
     Voltage,
+    Power,
+    Current,
+    Humidity,
+    Intrusion,
+    Pwm,
+}
+
+impl LmSensorsSensorType {
+    /// A lowercase name for the type, e.g. `temperature`.
+    fn as_str(self) -> &'static str {
+        match self {
+            LmSensorsSensorType::Temp => "temperature",
+            LmSensorsSensorType::Fan => "fan",
+            LmSensorsSensorType::Voltage => "voltage",
+            LmSensorsSensorType::Power => "power",
+            LmSensorsSensorType::Current => "current",
+            LmSensorsSensorType::Humidity => "humidity",
+            LmSensorsSensorType::Intrusion => "intrusion",
+            LmSensorsSensorType::Pwm => "pwm",
+        }
+    }
 }
 
-fn get_lm_sensor_data() -> Vec<LmSensorsDevice> {
+/// How long to wait for `sensors` to finish before giving up on it.
+const DEFAULT_SENSORS_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// The `sensors` executable to run if no path is given, looked up from `PATH`.
+const DEFAULT_SENSORS_PATH: &str = "sensors";
+
+/// Collects devices from lm_sensors. If `sensors_path` is given, that `sensors`
+/// executable is always run; otherwise libsensors is preferred if available.
+///
+/// If `chips` isn't empty, only those chips (e.g. `coretemp-isa-0000`) are read,
+/// by passing them on to `sensors`. libsensors isn't used in this case.
+///
+/// Collecting gives up after `timeout`, whether through libsensors or `sensors`.
+fn get_lm_sensor_data(
+    sensors_path: Option<&Path>, chips: &[String], timeout: Duration,
+) -> Result<(Vec<LmSensorsDevice>, Option<String>)> {
     if cfg!(target_os = "windows") {
-        return Vec::<LmSensorsDevice>::new();
+        return Err(unsupported_platform());
     }
 
-    let command = Command::new("sensors").arg("-u").output();
-    let output = match command {
-        Ok(val) => String::from_utf8(val.stdout).expect("error"),
-        Err(_) => "error".to_string(),
-    };
+    // Prefer reading from libsensors directly, and only fall back to parsing the
+    // output of `sensors` if it isn't available.
+    #[cfg(unix)]
+    if sensors_path.is_none() && chips.is_empty() {
+        if let Some(devices) = with_deadline(timeout, libsensors::read_devices)? {
+            return Ok((devices, None));
+        }
+    }
+
+    let sensors_path = sensors_path.unwrap_or(Path::new(DEFAULT_SENSORS_PATH));
+
+    // Newer versions of lm_sensors support structured JSON output; older ones will
+    // print nothing to stdout for the unknown flag, so fall back to `-u`.
+    let mut json_warning = None;
+    if supports_json(sensors_path) != Some(false) {
+        let output = run_sensors(sensors_path, &sensors_args("-j", chips)?, timeout)?;
+        if record_json_support(sensors_path, &output.stdout) {
+            return Ok((parse_lm_sensors_json(&output.stdout), output.warning));
+        }
+        json_warning = output.warning;
+    }
+
+    let output = run_sensors(sensors_path, &sensors_args("-u", chips)?, timeout)?;
+    Ok((
+        parse_lm_sensors_data(&output.stdout),
+        output.warning.or(json_warning),
+    ))
+}
+
+/// Whether each `sensors` executable supports `-j`, so that it's only checked
+/// on the first run rather than spawning `sensors` twice every time.
+static JSON_SUPPORT: OnceLock<Mutex<HashMap<PathBuf, bool>>> = OnceLock::new();
+
+/// Returns whether `sensors_path` supports `-j`, or [`None`] if it hasn't been
+/// run with it yet.
+fn supports_json(sensors_path: &Path) -> Option<bool> {
+    let json_support = JSON_SUPPORT.get_or_init(Default::default);
+    let json_support = json_support
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+    json_support.get(sensors_path).copied()
+}
+
+/// Returns whether the output of `sensors_path -j` is JSON, and if this is the
+/// first run, remembers that for [`supports_json`].
+fn record_json_support(sensors_path: &Path, stdout: &str) -> bool {
+    let is_json = is_json_output(stdout);
+
+    let json_support = JSON_SUPPORT.get_or_init(Default::default);
+    json_support
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .entry(sensors_path.to_path_buf())
+        .or_insert(is_json);
+
+    is_json
+}
+
+/// Builds the arguments for `sensors`: the output flag, followed by the chips to
+/// read (if any). Chip names are checked so that they can't be taken as flags.
+fn sensors_args<'a>(flag: &'a str, chips: &'a [String]) -> Result<Vec<&'a str>> {
+    let mut args = vec![flag];
+
+    for chip in chips {
+        if !is_valid_chip_name(chip) {
+            bail!("'{chip}' is not a valid chip name");
+        }
+        args.push(chip);
+    }
+
+    Ok(args)
+}
+
+/// Returns whether `chip` looks like a chip name that `sensors` accepts, e.g.
+/// `coretemp-isa-0000` or `nct6798-*`.
+fn is_valid_chip_name(chip: &str) -> bool {
+    !chip.is_empty()
+        && !chip.starts_with('-')
+        && chip
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '*'))
+}
+
+/// The output of a `sensors` run.
+struct SensorsOutput {
+    stdout: String,
+    /// A warning if `sensors` exited unsuccessfully. It still prints the chips
+    /// it could read if only some of them failed, so this isn't an error.
+    warning: Option<String>,
+}
+
+impl SensorsOutput {
+    fn new(sensors_path: &Path, output: &Output) -> Self {
+        Self {
+            // Odd locales or drivers can produce labels that aren't valid UTF-8;
+            // replace those bytes rather than throwing away every other reading.
+            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+            warning: (!output.status.success()).then(|| {
+                format!(
+                    "`{}` exited with {}, so some sensors may be missing",
+                    sensors_path.display(),
+                    output.status
+                )
+            }),
+        }
+    }
+}
+
+/// Runs `sensors` with the given arguments and returns its stdout, along with a
+/// warning if it exited unsuccessfully.
+fn run_sensors(sensors_path: &Path, args: &[&str], timeout: Duration) -> Result<SensorsOutput> {
+    // Force untranslated output, as we parse labels like `Adapter:`.
+    let output = output_with_timeout(
+        Command::new(sensors_path).args(args).env("LC_ALL", "C"),
+        timeout,
+    )
+    .with_context(|| format!("failed to run `{}`", sensors_path.display()))?;
+
+    Ok(SensorsOutput::new(sensors_path, &output))
+}
+
+/// Runs `f` on another thread, giving up on it if it doesn't finish within
+/// `timeout`. This is for libsensors, which can hang on a stuck i2c bus just like
+/// `sensors`. The thread can't be cancelled, so it's left to finish on its own.
+#[cfg(unix)]
+fn with_deadline<T: Send + 'static>(
+    timeout: Duration, f: impl FnOnce() -> T + Send + 'static,
+) -> Result<T> {
+    use std::sync::mpsc::{self, RecvTimeoutError};
+
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let _ = sender.send(f());
+    });
 
-    match output == *"error" {
-        true => Vec::<LmSensorsDevice>::new(),
-        false => parse_lm_sensors_data(output.as_str()),
+    match receiver.recv_timeout(timeout) {
+        Ok(value) => Ok(value),
+        Err(RecvTimeoutError::Timeout) => bail!("timed out after {timeout:?}"),
+        Err(RecvTimeoutError::Disconnected) => bail!("failed to read sensors"),
     }
 }
 
+/// Runs a command and collects its stdout, killing it if it doesn't finish
+/// within `timeout`. This is needed as `sensors` can hang (e.g. on a stuck i2c
+/// bus), which would otherwise stall data collection.
+fn output_with_timeout(command: &mut Command, timeout: Duration) -> Result<Output> {
+    let mut child = command
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    // Read stdout on another thread so the child can't block on a full pipe while
+    // we wait on it.
+    let Some(mut stdout) = child.stdout.take() else {
+        bail!("failed to capture stdout");
+    };
+    let reader = thread::spawn(move || {
+        let mut buffer = Vec::new();
+        stdout.read_to_end(&mut buffer).map(|_| buffer)
+    });
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            bail!("timed out after {timeout:?}");
+        }
+
+        thread::sleep(Duration::from_millis(10));
+    };
+
+    let stdout = reader
+        .join()
+        .map_err(|_| anyhow!("failed to read stdout"))??;
+
+    Ok(Output {
+        status,
+        stdout,
+        stderr: Vec::new(),
+    })
+}
+
+/// Returns whether the `sensors` output is JSON, based on the first
+/// non-whitespace byte.
+fn is_json_output(data: &str) -> bool {
+    data.trim_start().starts_with('{')
+}
+
 fn parse_lm_sensors_sensor_type(sensor_name: &str) -> LmSensorsSensorType {
     if sensor_name.contains("temp") {
         LmSensorsSensorType::Temp
     } else if sensor_name.contains("fan") {
         LmSensorsSensorType::Fan
+    } else if sensor_name.contains("power") {
+        LmSensorsSensorType::Power
+    } else if sensor_name.contains("curr") {
+        LmSensorsSensorType::Current
+    } else if sensor_name.contains("humidity") {
+        LmSensorsSensorType::Humidity
+    } else if sensor_name.contains("intrusion") {
+        LmSensorsSensorType::Intrusion
+    } else if sensor_name.contains("pwm") {
+        LmSensorsSensorType::Pwm
     } else {
         LmSensorsSensorType::Voltage
     }
 }
 
-fn format_friendly_names(device_name: String, sensor_name: String) -> String {
-    let parent_name = match device_name.clone().to_lowercase() {
-        x if x.contains("wifi") => "Wifi".to_string(),
-        x if x.contains("gpu") => "Gpu".to_string(),
-        x if x.contains("nvidia") => "Gpu".to_string(),
-        x if x.contains("it86") => "MB".to_string(),
-        x if x.contains("k10") => "CPU".to_string(),
-        x if x.contains("kraken") => "AIO".to_string(),
-        x if x.contains("nvme") => "Nvme".to_string(),
+/// Formats a sensor name as `"<parent>: <sensor>"`, where the parent is derived
+/// from the device name. User-provided `name_rules` are checked in order before
+/// the built-in defaults, and the first matching rule wins. If nothing matches,
+/// the first part of the device name is used.
+fn format_friendly_names(device_name: &str, sensor_name: &str, name_rules: &[NameRule]) -> String {
+    if let Some(rule) = name_rules.iter().find(|rule| rule.is_match(device_name)) {
+        return format!("{0}: {1}", rule.label, sensor_name);
+    }
+
+    let lowercase_name = device_name.to_lowercase();
+
+    // Short family names are matched against just the chip name (e.g. `k10temp`
+    // in `k10temp-pci-00c3`), so they don't match by coincidence elsewhere.
+    let chip_name = lowercase_name.split('-').next().unwrap_or_default();
+
+    let parent_name = match lowercase_name.as_str() {
+        x if x.contains("wifi") => "Wifi",
+        x if x.contains("amdgpu") => {
+            return format!("Gpu: {}", amdgpu_sensor_name(sensor_name));
+        }
+        x if x.contains("gpu") => "Gpu",
+        x if x.contains("nvidia") => "Gpu",
+        x if x.contains("nouveau") => "Gpu",
+        _ if chip_name.starts_with("it86") => "MB",
+        _ if chip_name == "k10temp" => "CPU",
+        x if x.contains("coretemp") => {
+            return format!("CPU: {}", coretemp_sensor_name(sensor_name));
+        }
+        x if x.contains("kraken") => "AIO",
+        x if x.contains("nvme") => "Nvme",
         _ => device_name
             .split('-')
             .next()
-            .expect("device name")
-            .to_string(),
+            .filter(|prefix| !prefix.is_empty())
+            .unwrap_or(device_name),
     };
 
     format!("{0}: {1}", parent_name, sensor_name)
 }
 
+/// Expands the short labels amdgpu uses for its temperature sensors, e.g. `mem`
+/// for the VRAM temperature. Other labels are kept.
+fn amdgpu_sensor_name(sensor_name: &str) -> &str {
+    match sensor_name {
+        "edge" => "Edge",
+        "junction" => "Junction",
+        "mem" => "Memory",
+        _ => sensor_name,
+    }
+}
+
+/// Shortens Intel coretemp's `Package id N` label to `Package` for the first
+/// package, or `Package N` for the others. Other labels (e.g. `Core 0`) are kept.
+fn coretemp_sensor_name(sensor_name: &str) -> String {
+    match sensor_name.strip_prefix("Package id ") {
+        Some("0") => "Package".to_string(),
+        Some(id) => format!("Package {id}"),
+        None => sensor_name.to_string(),
+    }
+}
+
+/// Builds a sensor from the subfield lines (e.g. `temp1_input: 45.000`) that
+/// belong to it. The `_input` subfield is used as the reading, and any `_max`
+/// and `_crit` subfields with the same prefix are used as thresholds. The sensor
+/// is flagged as alarming if any `_alarm` subfield with the same prefix (e.g.
+/// `temp1_alarm` or `temp1_crit_alarm`) is non-zero.
+///
+/// Intrusion sensors don't have an `_input` subfield, so their `_alarm`
+/// subfield is used as the reading instead. Likewise, PWM sensors use their bare
+/// `pwmN` subfield. Fans that only report a `_fault` subfield are kept without a
+/// reading, so they can be shown as not present.
+fn parse_lm_sensors_sensor(name: String, subfields: &[(&str, &str)]) -> Option<LmSensorsSensor> {
+    let (input_key, input_value) = subfields
+        .iter()
+        .find(|(key, _)| key.ends_with("_input"))
+        .or_else(|| {
+            subfields
+                .iter()
+                .find(|(key, _)| key.starts_with("intrusion") && key.ends_with("_alarm"))
+        })
+        .or_else(|| subfields.iter().find(|(key, _)| is_pwm_key(key)))
+        .or_else(|| {
+            subfields
+                .iter()
+                .find(|(key, _)| key.starts_with("fan") && key.ends_with("_fault"))
+        })?;
+    let prefix = input_key
+        .strip_suffix("_input")
+        .or_else(|| input_key.strip_suffix("_alarm"))
+        .or_else(|| input_key.strip_suffix("_fault"))
+        .unwrap_or(input_key);
+
+    let threshold = |suffix: &str| {
+        subfields
+            .iter()
+            .find(|(key, _)| key.strip_prefix(prefix) == Some(suffix))
+            .and_then(|(_, value)| value.parse().ok())
+    };
+
+    let alarm = subfields.iter().any(|(key, value)| {
+        key.strip_prefix(prefix)
+            .is_some_and(|suffix| suffix.starts_with('_') && suffix.ends_with("_alarm"))
+            && value.parse::<f32>().is_ok_and(|value| value != 0.0)
+    });
+
+    let sensor_type = parse_lm_sensors_sensor_type(input_key);
+    let value = if input_key.ends_with("_fault") {
+        None
+    } else {
+        parse_lm_sensors_value(input_value)
+    };
+
+    Some(LmSensorsSensor {
+        name,
+        prefix: prefix.to_string(),
+        value,
+        sensor_type,
+        high: threshold("_max"),
+        crit: threshold("_crit"),
+        min: threshold("_min"),
+        alarm,
+        crit_alarm: threshold("_crit_alarm").is_some_and(|alarm: f32| alarm != 0.0),
+        fault: threshold("_fault").is_some_and(|fault: f32| fault != 0.0),
+        beep: threshold("_beep").map(|beep: f32| beep != 0.0),
+        divisor: threshold("_div"),
+    })
+}
+
+/// Collapses duplicate sensors within a device, e.g. when a chip lists both
+/// `temp1` and a relabeled alias of it. Sensors are duplicates if they have the
+/// same type and reading, and either the same name or the same subfields. Of
+/// the two, a label is kept over a generic name like `temp1`. Sensors with
+/// different readings are always kept, as they're likely separate sensors.
+fn dedup_sensors(sensors: &mut Vec<LmSensorsSensor>) {
+    let mut deduped: Vec<LmSensorsSensor> = Vec::with_capacity(sensors.len());
+
+    for sensor in sensors.drain(..) {
+        let duplicate = deduped.iter_mut().find(|kept| {
+            kept.sensor_type == sensor.sensor_type
+                && kept.value == sensor.value
+                && (kept.name == sensor.name || kept.prefix == sensor.prefix)
+        });
+
+        match duplicate {
+            Some(kept) => {
+                if kept.name == kept.prefix && sensor.name != sensor.prefix {
+                    kept.name = sensor.name;
+                }
+            }
+            None => deduped.push(sensor),
+        }
+    }
+
+    *sensors = deduped;
+}
+
+/// Returns whether a subfield is a bare PWM value, e.g. `pwm1`.
+fn is_pwm_key(key: &str) -> bool {
+    key.strip_prefix("pwm")
+        .is_some_and(|number| !number.is_empty() && number.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// Parses a sensor value, returning [`None`] if it's unparseable (e.g. `N/A`)
+/// or not finite. Scientific notation (e.g. `1.234e-03`, which `sensors` uses
+/// for tiny values) is accepted, and `-0.0` is normalized to `0.0` so it isn't
+/// displayed with a sign.
+///
+/// Temperatures aren't range checked here; see
+/// [`LmSensorsSnapshot::with_valid_range`].
+fn parse_lm_sensors_value(value: &str) -> Option<f32> {
+    let value: f32 = normalize_decimal_comma(value).parse().ok()?;
+
+    if !value.is_finite() {
+        None
+    } else if value == 0.0 {
+        Some(0.0)
+    } else {
+        Some(value)
+    }
+}
+
+/// Turns a comma decimal separator (e.g. `54,5`) into a dot, in case `sensors`
+/// ignores `LC_ALL=C`. This only applies if there's exactly one comma, no dot,
+/// and only digits on either side of it.
+///
+/// A comma followed by a group of three digits (e.g. `1,234`) could also be a
+/// thousands separator, so for any type of sensor, it's left alone (and so
+/// doesn't parse) unless the part before it can't be a thousands group, e.g.
+/// `0,500` or `1234,000`.
+fn normalize_decimal_comma(value: &str) -> Cow<'_, str> {
+    let Some((integer, fraction)) = value.split_once(',') else {
+        return Cow::Borrowed(value);
+    };
+
+    let is_digits = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit());
+    let digits = integer.strip_prefix('-').unwrap_or(integer);
+    if value.contains('.') || !is_digits(digits) || !is_digits(fraction) {
+        return Cow::Borrowed(value);
+    }
+
+    let is_thousands_group = fraction.len() == 3 && digits.len() <= 3 && !digits.starts_with('0');
+    if is_thousands_group {
+        Cow::Borrowed(value)
+    } else {
+        Cow::Owned(format!("{integer}.{fraction}"))
+    }
+}
+
+/// Parses the adapter line that follows a device header (e.g. `Adapter: PCI
+/// adapter`). This is tolerant of translated labels and spacing (e.g.
+/// `Adaptateur : Interface PCI`), so it accepts any unindented `label: value`
+/// line; sensor names are told apart as they have nothing after the colon.
+fn parse_adapter_line(line: &str) -> Option<&str> {
+    if line.starts_with(char::is_whitespace) {
+        return None;
+    }
+
+    let (label, adapter) = line.split_once(':')?;
+    let adapter = adapter.trim();
+
+    (!label.trim().is_empty() && !adapter.is_empty()).then_some(adapter)
+}
+
+/// Parses the error `sensors` prints in place of a subfield it failed to read
+/// (e.g. from a bad i2c read), returning the subfield's name. For example,
+/// `ERROR: Can't get value of subfeature temp1_input: Can't read` gives
+/// `temp1_input`.
+fn parse_subfeature_error(line: &str) -> Option<&str> {
+    let (key, _) = line
+        .trim()
+        .strip_prefix("ERROR: Can't get value of subfeature ")?
+        .split_once(':')?;
+
+    Some(key.trim())
+}
+
+/// Returns whether a line is a device header. These are chip names of the form
+/// `prefix-bus-address` (e.g. `nvme-pci-0100`), which aren't indented and don't
+/// end in `:` like sensor names do.
+fn is_device_header(line: &str) -> bool {
+    !line.is_empty()
+        && !line.contains(char::is_whitespace)
+        && !line.ends_with(':')
+        && line.split('-').filter(|part| !part.is_empty()).count() >= 3
+}
+
 fn parse_lm_sensors_data(data: &str) -> Vec<LmSensorsDevice> {
+    // Drop any BOM and CRLF line endings (e.g. under WSL) so they don't throw off
+    // device header detection.
+    let data = data
+        .trim_start_matches('\u{feff}')
+        .trim_end()
+        .replace("\r\n", "\n");
+
     let mut devices = Vec::new();
-    let mut lines = data.lines();
+    let mut lines = data.lines().peekable();
 
     while let Some(line) = lines.next() {
         // Look for device name (e.g., "iwlwifi_1-virtual-0")
-        if line.contains("-") {
+        if is_device_header(line) {
             let device_name = line.to_string();
+            // Some devices don't have an adapter line, so only consume it if it's there.
             let adapter = lines
-                .next()
-                .unwrap_or("")
-                .replace("Adapter: ", "")
+                .next_if(|line| parse_adapter_line(line).is_some())
+                .and_then(parse_adapter_line)
+                .unwrap_or_default()
                 .to_string();
 
             let mut sensors = Vec::new();
-            while let Some(sensor_line) = lines.next() {
+            // Stop at the next device header too, in case a device is missing its
+            // trailing blank line.
+            while let Some(sensor_line) = lines.next_if(|line| !is_device_header(line)) {
                 if sensor_line.trim().is_empty() {
+                    // Some versions of `sensors` print extra blank lines, so only
+                    // treat one as the end of the device section once it has a sensor.
+                    if sensors.is_empty() {
+                        continue;
+                    }
                     break; // end of the device section
                 }
 
                 // Parse sensor data
                 if sensor_line.trim().ends_with(":") {
                     let sensor_name = sensor_line.trim().trim_end_matches(':').to_string();
-                    if let Some(value_line) = lines.next() {
-                        match value_line.contains("input") {
-                            true => {
-                                let parts: Vec<&str> =
-                                    value_line.trim_start().split_whitespace().collect();
-                                if parts.len() == 2 {
-                                    let sensor_value: f32 = parts[1].parse().unwrap_or(0.0);
-                                    let sensor_type = parse_lm_sensors_sensor_type(parts[0]);
-                                    sensors.push(LmSensorsSensor {
-                                        name: sensor_name,
-                                        value: sensor_value,
-                                        sensor_type,
-                                    });
-                                }
-                            }
-                            false => {
-                                continue;
-                            }
-                        };
+
+                    // Subfields are the indented lines under the sensor name, and can be in
+                    // any order (e.g. `temp1_crit` may come before `temp1_input`).
+                    let mut subfields = Vec::new();
+                    while let Some(subfield_line) = lines.next_if(|line| {
+                        (line.starts_with(char::is_whitespace) && !line.trim().is_empty())
+                            || parse_subfeature_error(line).is_some()
+                    }) {
+                        if let Some(key) = parse_subfeature_error(subfield_line) {
+                            // Keep the sensor, but without a reading.
+                            subfields.push((key, "N/A"));
+                        } else if let Some((key, value)) = subfield_line.trim().split_once(':') {
+                            subfields.push((key, value.trim()));
+                        }
+                    }
+
+                    if let Some(sensor) = parse_lm_sensors_sensor(sensor_name, &subfields) {
+                        sensors.push(sensor);
                     }
                 }
             }
@@ -127,28 +812,3335 @@ fn parse_lm_sensors_data(data: &str) -> Vec<LmSensorsDevice> {
         }
     }
 
-    devices
+    // If `sensors` was cut off mid-write (e.g. by SIGPIPE), the last device may
+    // only have its header, so drop it rather than showing an empty device.
+    if devices
+        .last()
+        .is_some_and(|device| device.sensors.is_empty())
+    {
+        devices.pop();
+    }
+
+    merge_duplicate_devices(devices)
+}
+
+/// Merges devices with the same name into the first one, as some systems list
+/// the same chip twice (e.g. when a module is loaded under two names). If both
+/// have a sensor with the same name, the later one wins.
+fn merge_duplicate_devices(devices: Vec<LmSensorsDevice>) -> Vec<LmSensorsDevice> {
+    let mut merged: Vec<LmSensorsDevice> = Vec::with_capacity(devices.len());
+
+    for device in devices {
+        let Some(existing) = merged
+            .iter_mut()
+            .find(|existing| existing.name == device.name)
+        else {
+            merged.push(device);
+            continue;
+        };
+
+        if existing.adapter.is_empty() {
+            existing.adapter = device.adapter;
+        }
+
+        for sensor in device.sensors {
+            match existing
+                .sensors
+                .iter_mut()
+                .find(|existing| existing.name == sensor.name)
+            {
+                Some(existing) => *existing = sensor,
+                None => existing.sensors.push(sensor),
+            }
+        }
+    }
+
+    merged
 }
 
-pub fn get_temperature_data(
-    temp_type: &TemperatureType, filter: &Option<Filter>,
-) -> Result<Option<Vec<TempHarvest>>> {
-    let mut temperatures: Vec<TempHarvest> = vec![];
-
-    let sensor_data = get_lm_sensor_data();
-
-    sensor_data.iter().for_each(|device| {
-        device.sensors.iter().for_each(|sensor| {
-            if let LmSensorsSensorType::Temp = sensor.sensor_type {
-                if Filter::optional_should_keep(filter, &sensor.name) {
-                    temperatures.push(TempHarvest {
-                        name: format_friendly_names(device.name.clone(), sensor.name.clone()),
-                        temperature: Some(temp_type.convert_temp_unit(sensor.value)),
-                    })
+/// Returns the bus and address part of a device name, e.g. `pci-0100` for
+/// `nvme-pci-0100`.
+fn device_bus_id(device_name: &str) -> &str {
+    let mut parts = device_name.rsplitn(3, '-');
+
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(_), Some(_), Some(prefix)) => &device_name[prefix.len() + 1..],
+        _ => device_name,
+    }
+}
+
+/// Parses the output of `sensors -j`, which is structured as
+/// `{chip: {"Adapter": adapter, feature: {subfeature: value}}}`.
+fn parse_lm_sensors_json(data: &str) -> Vec<LmSensorsDevice> {
+    let Ok(chips) = serde_json::from_str::<Map<String, Value>>(data) else {
+        return Vec::new();
+    };
+
+    chips
+        .into_iter()
+        .filter_map(|(device_name, chip)| {
+            let Value::Object(features) = chip else {
+                return None;
+            };
+
+            let mut adapter = String::new();
+            let mut sensors = Vec::new();
+
+            for (feature_name, feature) in features {
+                match feature {
+                    Value::String(name) if feature_name == "Adapter" => adapter = name,
+                    Value::Object(subfeatures) => {
+                        // Stringify the values so we can build the sensor the same way as
+                        // when parsing the text output.
+                        let subfields = subfeatures
+                            .iter()
+                            .filter_map(|(key, value)| {
+                                value
+                                    .as_f64()
+                                    .map(|value| (key.as_str(), value.to_string()))
+                            })
+                            .collect::<Vec<_>>();
+                        let subfields = subfields
+                            .iter()
+                            .map(|(key, value)| (*key, value.as_str()))
+                            .collect::<Vec<_>>();
+
+                        if let Some(sensor) = parse_lm_sensors_sensor(feature_name, &subfields) {
+                            sensors.push(sensor);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+
+            Some(LmSensorsDevice {
+                name: device_name,
+                adapter,
+                sensors,
+            })
+        })
+        .collect()
+}
+
+/// The parsed result of a single `sensors` run. Temperatures, fans, and voltages
+/// are all read from the same snapshot, so collecting all three only spawns
+/// one process and keeps the readings consistent with each other.
+pub struct LmSensorsSnapshot {
+    devices: Vec<LmSensorsDevice>,
+    name_rules: Vec<NameRule>,
+    /// A warning about the run, e.g. if `sensors` reported a failure but still
+    /// printed some readings. See [`LmSensorsSnapshot::warning`].
+    warning: Option<String>,
+    /// Whether to name sensors like `MB: temp2` rather than by their raw names.
+    /// See [`LmSensorsSnapshot::with_friendly_names`].
+    friendly_names: bool,
+    /// Offsets (in Celsius) to add to temperature readings, by sensor id. See
+    /// [`LmSensorsSnapshot::with_calibration`].
+    calibration: HashMap<String, f32>,
+    /// Whether to leave out sensors that look unpopulated. See
+    /// [`LmSensorsSnapshot::with_hide_unpopulated`].
+    hide_unpopulated: bool,
+    /// The range (in Celsius) outside of which temperature readings are dropped,
+    /// if any. See [`LmSensorsSnapshot::with_valid_range`].
+    valid_range: Option<RangeInclusive<f32>>,
+    /// When the devices were read.
+    timestamp: Instant,
+}
+
+impl LmSensorsSnapshot {
+    /// Runs `sensors` and parses the output.
+    pub fn new() -> Result<Self> {
+        Self::with_timeout(DEFAULT_SENSORS_TIMEOUT)
+    }
+
+    /// Runs `sensors` and parses the output, giving up if `sensors` takes longer
+    /// than `timeout`.
+    pub fn with_timeout(timeout: Duration) -> Result<Self> {
+        Self::collect(None, &[], timeout)
+    }
+
+    /// Runs the `sensors` executable at `sensors_path` (e.g. if it isn't on
+    /// `PATH`) and parses the output, giving up if it takes longer than
+    /// `timeout`. libsensors is not used in this case.
+    pub fn with_sensors_path(sensors_path: &Path, timeout: Duration) -> Result<Self> {
+        Self::collect(Some(sensors_path), &[], timeout)
+    }
+
+    /// Runs `sensors` for only the given chips (e.g. `coretemp-isa-0000`), which
+    /// is quicker on machines with many chips. If `chips` is empty, every chip is
+    /// read as usual. libsensors is not used if any chips are given.
+    ///
+    /// Returns an error if a chip name is invalid.
+    pub fn with_chips(chips: &[String], timeout: Duration) -> Result<Self> {
+        Self::collect(None, chips, timeout)
+    }
+
+    /// Parses already-collected output from `sensors -u` or `sensors -j`,
+    /// without running anything.
+    pub fn from_sensors_output(data: &str) -> Self {
+        if is_json_output(data) {
+            Self::from_devices(parse_lm_sensors_json(data))
+        } else {
+            Self::from_devices(parse_lm_sensors_data(data))
+        }
+    }
+
+    /// Reads sensors straight from the hwmon sysfs interface, without running
+    /// `sensors` or using libsensors.
+    #[cfg(target_os = "linux")]
+    pub fn from_sysfs() -> Result<Self> {
+        Ok(Self::from_devices(sysfs::read_devices(Path::new(
+            sysfs::HWMON_PATH,
+        ))?))
+    }
+
+    fn collect(sensors_path: Option<&Path>, chips: &[String], timeout: Duration) -> Result<Self> {
+        let (devices, warning) = get_lm_sensor_data(sensors_path, chips, timeout)?;
+
+        Ok(Self {
+            warning,
+            ..Self::from_devices(devices)
+        })
+    }
+
+    /// Returns a warning about the run, if there was a problem that didn't stop
+    /// it from producing readings. For example, `sensors` exits unsuccessfully
+    /// if it fails to read some chips, but still prints the others.
+    pub fn warning(&self) -> Option<&str> {
+        self.warning.as_deref()
+    }
+
+    fn from_devices(mut devices: Vec<LmSensorsDevice>) -> Self {
+        for device in &mut devices {
+            dedup_sensors(&mut device.sensors);
+        }
+
+        Self {
+            devices,
+            name_rules: Vec::new(),
+            warning: None,
+            friendly_names: true,
+            calibration: HashMap::new(),
+            hide_unpopulated: false,
+            valid_range: None,
+            timestamp: Instant::now(),
+        }
+    }
+
+    /// Sets user-provided rules for naming devices, which take priority over the
+    /// built-in names. See [`format_friendly_names`].
+    pub fn with_name_rules(mut self, name_rules: Vec<NameRule>) -> Self {
+        self.name_rules = name_rules;
+        self
+    }
+
+    /// Sets whether to give sensors friendly names (e.g. `MB: temp2`), which is
+    /// the default. Otherwise, sensors are named by their raw device and sensor
+    /// names (e.g. `nct6798-isa-0290 / temp2`), which helps when debugging
+    /// hardware or writing precise filters.
+    pub fn with_friendly_names(mut self, friendly_names: bool) -> Self {
+        self.friendly_names = friendly_names;
+        self
+    }
+
+    /// Sets offsets to correct temperature sensors that read consistently high
+    /// or low, e.g. `-20.0` for a sensor that reads 20°C too high. Offsets are in
+    /// Celsius, applied before converting to another unit, and keyed by
+    /// [`TempHarvest::sensor_id`] so that they keep working if naming changes.
+    /// Thresholds aren't affected.
+    pub fn with_calibration(mut self, calibration: HashMap<String, f32>) -> Self {
+        self.calibration = calibration;
+        self
+    }
+
+    /// Sets whether to leave out sensors that look unpopulated, which many
+    /// boards expose: voltage rails reading exactly 0V, and fan headers reading
+    /// 0 RPM with their fault flag set. Unlike a [`Filter`], this goes by the
+    /// reading rather than the name. Temperatures are never left out.
+    pub fn with_hide_unpopulated(mut self, hide_unpopulated: bool) -> Self {
+        self.hide_unpopulated = hide_unpopulated;
+        self
+    }
+
+    /// Sets a range of temperatures (in Celsius, e.g.
+    /// [`PLAUSIBLE_CELSIUS_RANGE`](super::PLAUSIBLE_CELSIUS_RANGE)) outside of
+    /// which readings are dropped as driver glitches, such as 65535°C from a bad
+    /// i2c read. The check is made after calibration. By default, every finite
+    /// reading is kept.
+    pub fn with_valid_range(mut self, valid_range: RangeInclusive<f32>) -> Self {
+        self.valid_range = Some(valid_range);
+        self
+    }
+
+    /// Returns the sensors that pass the filter, along with their friendly
+    /// names. If multiple devices would produce the same name for the same type
+    /// of sensor, each is suffixed with its device's bus id so they can be told
+    /// apart.
+    fn named_sensors(
+        &self, filter: &Option<Filter>,
+    ) -> Vec<(&LmSensorsDevice, &LmSensorsSensor, String)> {
+        let mut named_sensors = vec![];
+
+        self.devices.iter().for_each(|device| {
+            device.sensors.iter().for_each(|sensor| {
+                if should_keep_sensor(filter, &device.name, &sensor.name)
+                    && !(self.hide_unpopulated && is_unpopulated(sensor))
+                {
+                    let name = if self.friendly_names {
+                        format_friendly_names(&device.name, &sensor.name, &self.name_rules)
+                    } else {
+                        format!("{} / {}", device.name, sensor.name)
+                    };
+                    named_sensors.push((device, sensor, name));
                 }
+            });
+        });
+
+        let collisions = {
+            let mut name_counts: HashMap<(LmSensorsSensorType, &str), usize> = HashMap::new();
+            for (_, sensor, name) in &named_sensors {
+                *name_counts
+                    .entry((sensor.sensor_type, name.as_str()))
+                    .or_default() += 1;
+            }
+
+            named_sensors
+                .iter()
+                .map(|(_, sensor, name)| name_counts[&(sensor.sensor_type, name.as_str())] > 1)
+                .collect::<Vec<_>>()
+        };
+
+        for ((device, _, name), collides) in named_sensors.iter_mut().zip(collisions) {
+            if collides {
+                *name = format!("{name} ({})", device_bus_id(&device.name));
             }
+        }
+
+        named_sensors
+    }
+
+    /// Returns the named sensors of just one type. See
+    /// [`LmSensorsSnapshot::named_sensors`].
+    fn named_sensors_of_type(
+        &self, sensor_type: LmSensorsSensorType, filter: &Option<Filter>,
+    ) -> impl Iterator<Item = (&LmSensorsDevice, &LmSensorsSensor, String)> {
+        self.named_sensors(filter)
+            .into_iter()
+            .filter(move |(_, sensor, _)| sensor.sensor_type == sensor_type)
+    }
+
+    /// Builds a temperature harvest from a sensor, applying any calibration.
+    fn temp_harvest(
+        &self, device: &LmSensorsDevice, sensor: &LmSensorsSensor, name: String,
+        temp_type: &TemperatureType,
+    ) -> TempHarvest {
+        let sensor_id = sensor_id(device, sensor);
+        let offset = self.calibration.get(&sensor_id).copied().unwrap_or(0.0);
+
+        let temperature = sensor.value.and_then(|value| {
+            temp_type.convert_valid_temp_unit(value + offset, self.valid_range.as_ref())
         });
-    });
+        let crit = sensor.crit.map(|crit| temp_type.convert_temp_unit(crit));
+
+        // Without a critical alarm subfield, this falls back to just comparing the
+        // reading against the threshold.
+        let throttling = sensor.crit_alarm
+            || temperature
+                .zip(crit)
+                .is_some_and(|(temperature, crit)| temperature >= crit);
+
+        TempHarvest {
+            name,
+            sensor_id,
+            temperature,
+            unit: *temp_type,
+            high: sensor.high.map(|high| temp_type.convert_temp_unit(high)),
+            crit,
+            chip: Some(device.name.clone()),
+            adapter: (!device.adapter.is_empty()).then(|| device.adapter.clone()),
+            timestamp: Some(self.timestamp),
+            alarm: sensor.alarm,
+            throttling,
+            beep_enabled: sensor.beep,
+            ..Default::default()
+        }
+    }
+
+    /// Returns all temperature sensors, converted to `temp_type`.
+    pub fn temperatures(
+        &self, temp_type: &TemperatureType, filter: &Option<Filter>,
+    ) -> Vec<TempHarvest> {
+        let mut temperatures = Vec::new();
+        self.for_each_temperature(temp_type, filter, |temp| temperatures.push(temp));
+        temperatures
+    }
+
+    /// Calls `f` with each temperature sensor, converted to `temp_type`, rather
+    /// than collecting them into a [`Vec`]. This is the same set of sensors, in
+    /// the same order, as [`LmSensorsSnapshot::temperatures`].
+    pub fn for_each_temperature(
+        &self, temp_type: &TemperatureType, filter: &Option<Filter>, mut f: impl FnMut(TempHarvest),
+    ) {
+        for (device, sensor, name) in self.named_sensors_of_type(LmSensorsSensorType::Temp, filter)
+        {
+            f(self.temp_harvest(device, sensor, name, temp_type));
+        }
+    }
+
+    /// Returns temperature sensors grouped by device, in the order `sensors`
+    /// reports them. Devices without any temperature sensors that pass the
+    /// filter are left out.
+    pub fn temperature_tree(
+        &self, temp_type: &TemperatureType, filter: &Option<Filter>,
+    ) -> Vec<DeviceTemps> {
+        let mut tree: Vec<DeviceTemps> = Vec::new();
+
+        for (device, sensor, name) in self.named_sensors_of_type(LmSensorsSensorType::Temp, filter)
+        {
+            let harvest = self.temp_harvest(device, sensor, name, temp_type);
+
+            match tree.last_mut() {
+                Some(last) if last.device == device.name => last.sensors.push(harvest),
+                _ => tree.push(DeviceTemps {
+                    device: device.name.clone(),
+                    adapter: harvest.adapter.clone(),
+                    sensors: vec![harvest],
+                }),
+            }
+        }
 
-    Ok(Some(temperatures))
+        tree
+    }
+
+    /// Returns fan speeds (in RPM). Stopped fans that report zero RPM are still
+    /// included, as are headers without a fan; see [`FanHarvest::present`].
+    pub fn fans(&self, filter: &Option<Filter>) -> Vec<FanHarvest> {
+        self.named_sensors_of_type(LmSensorsSensorType::Fan, filter)
+            .map(|(device, sensor, name)| fan_harvest(device, sensor, name))
+            .collect()
+    }
+
+    /// Returns voltages. Negative rails (e.g. -12V) keep their sign, and rails
+    /// without a reading are still included.
+    pub fn voltages(&self, filter: &Option<Filter>) -> Vec<VoltageHarvest> {
+        self.named_sensors_of_type(LmSensorsSensorType::Voltage, filter)
+            .map(|(device, sensor, name)| voltage_harvest(device, sensor, name))
+            .collect()
+    }
+
+    /// Returns power draw, in watts. Sensors without a reading are still
+    /// included.
+    pub fn powers(&self, filter: &Option<Filter>) -> Vec<PowerHarvest> {
+        self.named_sensors_of_type(LmSensorsSensorType::Power, filter)
+            .map(|(device, sensor, name)| power_harvest(device, sensor, name))
+            .collect()
+    }
+
+    /// Returns currents, in amps.
+    pub fn currents(&self, filter: &Option<Filter>) -> Vec<CurrentHarvest> {
+        self.named_sensors_of_type(LmSensorsSensorType::Current, filter)
+            .filter_map(|(device, sensor, name)| current_harvest(device, sensor, name))
+            .collect()
+    }
+
+    /// Returns relative humidity percentages.
+    pub fn humidities(&self, filter: &Option<Filter>) -> Vec<HumidityHarvest> {
+        self.named_sensors_of_type(LmSensorsSensorType::Humidity, filter)
+            .filter_map(|(device, sensor, name)| humidity_harvest(device, sensor, name))
+            .collect()
+    }
+
+    /// Returns chassis intrusion sensors, and whether each has been triggered.
+    pub fn intrusions(&self, filter: &Option<Filter>) -> Vec<IntrusionHarvest> {
+        self.named_sensors_of_type(LmSensorsSensorType::Intrusion, filter)
+            .filter_map(|(device, sensor, name)| intrusion_harvest(device, sensor, name))
+            .collect()
+    }
+
+    /// Returns the raw and friendly names of every sensor that passes the
+    /// filter, tagged with its type, in the order `sensors` reports them.
+    pub fn sensor_names(&self, filter: &Option<Filter>) -> Vec<SensorName> {
+        self.named_sensors(filter)
+            .into_iter()
+            .map(|(device, sensor, name)| SensorName {
+                sensor_type: sensor.sensor_type.as_str(),
+                device: device.name.clone(),
+                raw_name: sensor.name.clone(),
+                friendly_name: name,
+            })
+            .collect()
+    }
+
+    /// Returns fan PWM duty cycles, as percentages.
+    pub fn pwms(&self, filter: &Option<Filter>) -> Vec<PwmHarvest> {
+        self.named_sensors_of_type(LmSensorsSensorType::Pwm, filter)
+            .filter_map(|(device, sensor, name)| pwm_harvest(device, sensor, name))
+            .collect()
+    }
+
+    /// Returns every sensor, partitioned by type in a single pass. Temperatures
+    /// are in Celsius.
+    pub fn bundle(&self, filter: &Option<Filter>) -> SensorBundle {
+        let mut bundle = SensorBundle {
+            warning: self.warning.clone(),
+            ..Default::default()
+        };
+
+        // This includes the same sensors as the per-type getters, e.g. fans
+        // without a reading are kept but currents without one are skipped.
+        for (device, sensor, name) in self.named_sensors(filter) {
+            match sensor.sensor_type {
+                LmSensorsSensorType::Temp => bundle.temperatures.push(self.temp_harvest(
+                    device,
+                    sensor,
+                    name,
+                    &TemperatureType::Celsius,
+                )),
+                LmSensorsSensorType::Fan => bundle.fans.push(fan_harvest(device, sensor, name)),
+                LmSensorsSensorType::Voltage => {
+                    bundle.voltages.push(voltage_harvest(device, sensor, name))
+                }
+                LmSensorsSensorType::Power => {
+                    bundle.powers.push(power_harvest(device, sensor, name))
+                }
+                LmSensorsSensorType::Current => {
+                    bundle
+                        .currents
+                        .extend(current_harvest(device, sensor, name));
+                }
+                LmSensorsSensorType::Humidity => {
+                    bundle
+                        .humidities
+                        .extend(humidity_harvest(device, sensor, name));
+                }
+                LmSensorsSensorType::Pwm => bundle.pwms.extend(pwm_harvest(device, sensor, name)),
+                LmSensorsSensorType::Intrusion => {
+                    bundle
+                        .intrusions
+                        .extend(intrusion_harvest(device, sensor, name));
+                }
+            }
+        }
+
+        bundle
+    }
+}
+
+/// Returns the stable id of a sensor, as `<device>/<sensor>`.
+fn sensor_id(device: &LmSensorsDevice, sensor: &LmSensorsSensor) -> String {
+    format!("{}/{}", device.name, sensor.name)
+}
+
+/// Whether to keep a sensor. The same filter is checked against both the sensor
+/// name and the device name (e.g. `acpitz-acpi-0`), so a single entry can match
+/// every sensor of a device.
+fn should_keep_sensor(filter: &Option<Filter>, device_name: &str, sensor_name: &str) -> bool {
+    match filter {
+        Some(filter) => {
+            if filter.has_match(sensor_name) || filter.has_match(device_name) {
+                !filter.ignore_matches()
+            } else {
+                filter.ignore_matches()
+            }
+        }
+        None => true,
+    }
+}
+
+/// Whether a sensor looks like it has nothing connected to it: a voltage rail
+/// reading exactly 0V, or a fan reading 0 RPM (or nothing) with its fault flag
+/// set.
+fn is_unpopulated(sensor: &LmSensorsSensor) -> bool {
+    match sensor.sensor_type {
+        LmSensorsSensorType::Voltage => sensor.value == Some(0.0),
+        LmSensorsSensorType::Fan => sensor.fault && sensor.value.map_or(true, |rpm| rpm == 0.0),
+        _ => false,
+    }
+}
+
+/// Drivers whose `fanN_input` doesn't already account for `fanN_div`, and so
+/// needs to be divided by it to get the real RPM.
+///
+/// All in-kernel hwmon drivers (e.g. `it87`, `nct6775`, `w83627ehf`) apply the
+/// divisor themselves as per the sysfs ABI, so this is empty; it exists for
+/// out-of-tree drivers that report readings assuming a divisor of 1. Entries are
+/// matched against the start of the device name.
+const FAN_DIVISOR_DRIVERS: &[&str] = &[];
+
+fn fan_harvest(device: &LmSensorsDevice, sensor: &LmSensorsSensor, name: String) -> FanHarvest {
+    let rpm = sensor
+        .value
+        .filter(|_| !sensor.fault)
+        .map(|rpm| scale_fan_rpm(&device.name, rpm, sensor.divisor, FAN_DIVISOR_DRIVERS));
+
+    FanHarvest {
+        name,
+        sensor_id: sensor_id(device, sensor),
+        rpm: rpm.unwrap_or(0.0),
+        present: rpm.is_some(),
+        alarm: sensor.alarm,
+        beep_enabled: sensor.beep,
+        divisor: sensor.divisor,
+        min: sensor.min,
+        max: sensor.high,
+    }
+}
+
+fn voltage_harvest(
+    device: &LmSensorsDevice, sensor: &LmSensorsSensor, name: String,
+) -> VoltageHarvest {
+    VoltageHarvest {
+        name,
+        sensor_id: sensor_id(device, sensor),
+        volts: sensor.value,
+        alarm: sensor.alarm,
+    }
+}
+
+fn power_harvest(device: &LmSensorsDevice, sensor: &LmSensorsSensor, name: String) -> PowerHarvest {
+    PowerHarvest {
+        name,
+        sensor_id: sensor_id(device, sensor),
+        watts: sensor.value,
+        alarm: sensor.alarm,
+    }
+}
+
+// Unlike fans, voltages, and powers, these sensors are left out if they don't
+// have a reading. Both the per-type getters and `bundle` build harvests through
+// these, so they always agree on which sensors are included.
+
+fn current_harvest(
+    device: &LmSensorsDevice, sensor: &LmSensorsSensor, name: String,
+) -> Option<CurrentHarvest> {
+    Some(CurrentHarvest {
+        name,
+        sensor_id: sensor_id(device, sensor),
+        amps: sensor.value?,
+        alarm: sensor.alarm,
+    })
+}
+
+fn humidity_harvest(
+    device: &LmSensorsDevice, sensor: &LmSensorsSensor, name: String,
+) -> Option<HumidityHarvest> {
+    Some(HumidityHarvest {
+        name,
+        sensor_id: sensor_id(device, sensor),
+        percent: sensor.value?,
+    })
+}
+
+fn intrusion_harvest(
+    device: &LmSensorsDevice, sensor: &LmSensorsSensor, name: String,
+) -> Option<IntrusionHarvest> {
+    Some(IntrusionHarvest {
+        name,
+        sensor_id: sensor_id(device, sensor),
+        triggered: sensor.value? != 0.0,
+    })
+}
+
+fn pwm_harvest(
+    device: &LmSensorsDevice, sensor: &LmSensorsSensor, name: String,
+) -> Option<PwmHarvest> {
+    Some(PwmHarvest {
+        name,
+        sensor_id: sensor_id(device, sensor),
+        percent: pwm_percent(sensor.value?),
+    })
+}
+
+/// Divides `rpm` by the fan's divisor if the device's driver is in `drivers`.
+fn scale_fan_rpm(device_name: &str, rpm: f32, divisor: Option<f32>, drivers: &[&str]) -> f32 {
+    match divisor {
+        Some(divisor)
+            if divisor > 0.0 && drivers.iter().any(|driver| device_name.starts_with(driver)) =>
+        {
+            rpm / divisor
+        }
+        _ => rpm,
+    }
+}
+
+/// Converts a raw PWM value (0 to 255) to a percentage.
+fn pwm_percent(raw: f32) -> f32 {
+    raw / 255.0 * 100.0
+}
+
+/// The temperature sensors of a single device, e.g. for showing each device as
+/// its own collapsible section.
+#[derive(Default, Debug, Clone)]
+pub struct DeviceTemps {
+    /// The device's name, e.g. `k10temp-pci-00c3`.
+    pub device: String,
+    pub adapter: Option<String>,
+    pub sensors: Vec<TempHarvest>,
+}
+
+/// Every type of sensor reading from a single `sensors` run.
+#[derive(Default, Debug, Clone)]
+pub struct SensorBundle {
+    /// Temperatures, in `unit`.
+    pub temperatures: Vec<TempHarvest>,
+    /// The unit that `temperatures` are in. This starts as Celsius.
+    pub unit: TemperatureType,
+    pub fans: Vec<FanHarvest>,
+    pub voltages: Vec<VoltageHarvest>,
+    pub powers: Vec<PowerHarvest>,
+    pub currents: Vec<CurrentHarvest>,
+    pub humidities: Vec<HumidityHarvest>,
+    pub pwms: Vec<PwmHarvest>,
+    pub intrusions: Vec<IntrusionHarvest>,
+    /// A warning about the run that collected this. See
+    /// [`LmSensorsSnapshot::warning`].
+    pub warning: Option<String>,
+}
+
+impl SensorBundle {
+    /// Converts all temperatures (and their thresholds) to `target`. This goes
+    /// through Celsius, so converting back and forth doesn't accumulate.
+    pub fn convert_temperatures(&mut self, target: TemperatureType) {
+        if self.unit == target {
+            return;
+        }
+
+        let unit = self.unit;
+        let convert = |value: &mut Option<f32>| {
+            if let Some(value) = value {
+                *value = target.convert_temp_unit(unit.to_celsius(*value));
+            }
+        };
+
+        for temp in &mut self.temperatures {
+            convert(&mut temp.temperature);
+            convert(&mut temp.high);
+            convert(&mut temp.crit);
+            temp.unit = target;
+        }
+
+        self.unit = target;
+    }
+
+    /// Returns how each sensor's reading changed from this bundle to `other`.
+    /// Sensors are matched by type and id (see [`SensorDelta::id`]), and ones
+    /// whose reading didn't change are left out. Removed and changed sensors come
+    /// first, in this bundle's order, followed by added ones in `other`'s order.
+    ///
+    /// Intrusion sensors aren't compared, as they don't have a reading.
+    pub fn diff(&self, other: &SensorBundle) -> Vec<SensorDelta> {
+        let old_readings = self.readings();
+        let new_readings = other.readings();
+        let old_by_id: HashMap<_, _> = old_readings
+            .iter()
+            .map(|&(sensor_type, id, value)| ((sensor_type, id), value))
+            .collect();
+        let new_by_id: HashMap<_, _> = new_readings
+            .iter()
+            .map(|&(sensor_type, id, value)| ((sensor_type, id), value))
+            .collect();
+
+        let mut deltas = Vec::new();
+
+        for &(sensor_type, id, old) in &old_readings {
+            let (change, new) = match new_by_id.get(&(sensor_type, id)) {
+                Some(&new) if new == old => continue,
+                Some(&new) => (SensorChange::Changed, new),
+                None => (SensorChange::Removed, None),
+            };
+
+            deltas.push(SensorDelta {
+                sensor_type,
+                id: id.to_string(),
+                change,
+                old,
+                new,
+            });
+        }
+
+        for &(sensor_type, id, new) in &new_readings {
+            if !old_by_id.contains_key(&(sensor_type, id)) {
+                deltas.push(SensorDelta {
+                    sensor_type,
+                    id: id.to_string(),
+                    change: SensorChange::Added,
+                    old: None,
+                    new,
+                });
+            }
+        }
+
+        deltas
+    }
+
+    /// Returns the type, id, and reading of every sensor but intrusion sensors.
+    fn readings(&self) -> Vec<(&'static str, &str, Option<f32>)> {
+        use LmSensorsSensorType::*;
+
+        let mut readings = Vec::new();
+
+        for temp in &self.temperatures {
+            readings.push((Temp.as_str(), temp.sensor_id.as_str(), temp.temperature));
+        }
+        for fan in &self.fans {
+            readings.push((Fan.as_str(), fan.sensor_id.as_str(), Some(fan.rpm)));
+        }
+        for voltage in &self.voltages {
+            readings.push((Voltage.as_str(), voltage.sensor_id.as_str(), voltage.volts));
+        }
+        for power in &self.powers {
+            readings.push((Power.as_str(), power.sensor_id.as_str(), power.watts));
+        }
+        for current in &self.currents {
+            readings.push((
+                Current.as_str(),
+                current.sensor_id.as_str(),
+                Some(current.amps),
+            ));
+        }
+        for humidity in &self.humidities {
+            readings.push((
+                Humidity.as_str(),
+                humidity.sensor_id.as_str(),
+                Some(humidity.percent),
+            ));
+        }
+        for pwm in &self.pwms {
+            readings.push((Pwm.as_str(), pwm.sensor_id.as_str(), Some(pwm.percent)));
+        }
+
+        readings
+    }
+}
+
+/// How a sensor differs between two bundles. See [`SensorBundle::diff`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SensorChange {
+    /// Only in the new bundle.
+    Added,
+    /// Only in the old bundle.
+    Removed,
+    /// In both bundles, with different readings.
+    Changed,
+}
+
+/// A change in one sensor between two bundles. See [`SensorBundle::diff`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SensorDelta {
+    /// The type of sensor, e.g. `temperature` or `fan`.
+    pub sensor_type: &'static str,
+    /// The sensor's stable id, e.g. [`TempHarvest::sensor_id`].
+    pub id: String,
+    pub change: SensorChange,
+    /// The reading in the old bundle, if it had one.
+    pub old: Option<f32>,
+    /// The reading in the new bundle, if it has one.
+    pub new: Option<f32>,
+}
+
+impl SensorDelta {
+    /// Returns how much the reading went up (or down, if negative), if there's
+    /// both an old and a new reading.
+    pub fn delta(&self) -> Option<f32> {
+        Some(self.new? - self.old?)
+    }
+}
+
+/// Returns the mean temperature of each device (by its raw chip name), in the
+/// bundle's unit and in the order devices first appear. Sensors without a
+/// reading are skipped, and devices with no readings at all are left out.
+pub fn device_averages(bundle: &SensorBundle) -> Vec<(String, f32)> {
+    let mut sums: Vec<(&str, f32, usize)> = Vec::new();
+
+    for temp in &bundle.temperatures {
+        let (Some(chip), Some(temperature)) = (&temp.chip, temp.temperature) else {
+            continue;
+        };
+
+        match sums.iter_mut().find(|(device, ..)| *device == chip) {
+            Some((_, sum, count)) => {
+                *sum += temperature;
+                *count += 1;
+            }
+            None => sums.push((chip, temperature, 1)),
+        }
+    }
+
+    sums.into_iter()
+        .map(|(device, sum, count)| (device.to_string(), sum / count as f32))
+        .collect()
+}
+
+/// How many of each main type of sensor are in a [`SensorBundle`], e.g. for a
+/// "12 temps, 4 fans" status line.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SensorCounts {
+    pub temps: usize,
+    pub fans: usize,
+    pub voltages: usize,
+    pub powers: usize,
+}
+
+/// Counts the sensors in the bundle by type. Sensors without a reading are
+/// still counted, as they're still detected sensors.
+pub fn sensor_counts(bundle: &SensorBundle) -> SensorCounts {
+    SensorCounts {
+        temps: bundle.temperatures.len(),
+        fans: bundle.fans.len(),
+        voltages: bundle.voltages.len(),
+        powers: bundle.powers.len(),
+    }
+}
+
+/// Returns just the temperature readings in `unit`, e.g. for plotting. Sensors
+/// without a reading are [`f32::NAN`], so that each value lines up with the
+/// name at the same index in [`temperature_names`].
+pub fn temperatures_as_f32_vec(bundle: &SensorBundle, unit: TemperatureType) -> Vec<f32> {
+    bundle
+        .temperatures
+        .iter()
+        .map(|temp| match temp.temperature {
+            Some(value) => unit.convert_temp_unit(bundle.unit.to_celsius(value)),
+            None => f32::NAN,
+        })
+        .collect()
+}
+
+/// Returns the temperature sensor names, in the same order as
+/// [`temperatures_as_f32_vec`].
+pub fn temperature_names(bundle: &SensorBundle) -> Vec<&str> {
+    bundle
+        .temperatures
+        .iter()
+        .map(|temp| temp.name.as_str())
+        .collect()
+}
+
+/// Returns every type of sensor reading from lm_sensors with one `sensors` run
+/// and one pass over the results. Temperatures are in Celsius.
+pub fn get_all_sensor_data(filter: &Option<Filter>) -> Result<SensorBundle> {
+    get_all_sensor_data_with_name_rules(filter, &[])
+}
+
+/// Like [`get_all_sensor_data`], but names sensors using `name_rules` before
+/// the built-in rules. This is what data collection uses, through
+/// [`SensorCache`].
+pub fn get_all_sensor_data_with_name_rules(
+    filter: &Option<Filter>, name_rules: &[NameRule],
+) -> Result<SensorBundle> {
+    Ok(LmSensorsSnapshot::new()?
+        .with_name_rules(name_rules.to_vec())
+        .bundle(filter))
+}
+
+/// Returns temperatures from lm_sensors. This is [`None`] if `sensors` isn't
+/// installed, and an empty list if it ran but found no sensors, so the two can be
+/// told apart. Any other failure to run `sensors` is returned as an error.
+///
+/// Sensors are in the order `sensors` reports them (grouped by device), unless
+/// `sort_by_name` is set.
+pub fn get_temperature_data(
+    temp_type: &TemperatureType, filter: &Option<Filter>, sort_by_name: bool,
+) -> Result<Option<Vec<TempHarvest>>> {
+    let snapshot = none_if_unavailable(LmSensorsSnapshot::new())?;
+
+    Ok(snapshot.map(|snapshot| {
+        let mut temperatures = snapshot.temperatures(temp_type, filter);
+        if sort_by_name {
+            sort_temperatures_by_name(&mut temperatures);
+        }
+        temperatures
+    }))
+}
+
+/// The error for trying to collect from lm_sensors on a platform it doesn't
+/// support, i.e. Windows.
+fn unsupported_platform() -> anyhow::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        "lm_sensors is not supported on Windows",
+    )
+    .into()
+}
+
+/// Maps an error from collecting to [`None`] if it's because lm_sensors isn't
+/// available, i.e. `sensors` isn't installed or the platform isn't supported, so
+/// callers can tell that apart from `sensors` failing.
+fn none_if_unavailable<T>(result: Result<T>) -> Result<Option<T>> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(err) if is_unavailable(&err) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+fn is_unavailable(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| {
+        cause.downcast_ref::<io::Error>().is_some_and(|err| {
+            matches!(
+                err.kind(),
+                io::ErrorKind::NotFound | io::ErrorKind::Unsupported
+            )
+        })
+    })
+}
+
+/// Calls `f` with each temperature from lm_sensors, e.g. to write them out
+/// directly without collecting them first. See
+/// [`LmSensorsSnapshot::for_each_temperature`].
+pub fn for_each_temperature(
+    temp_type: &TemperatureType, filter: &Option<Filter>, f: impl FnMut(TempHarvest),
+) -> Result<()> {
+    LmSensorsSnapshot::new()?.for_each_temperature(temp_type, filter, f);
+    Ok(())
+}
+
+/// Returns temperatures read straight from the hwmon sysfs interface. This is a
+/// drop-in for [`get_temperature_data`] that doesn't need the `sensors` binary,
+/// at the cost of not having adapter names. This is [`None`] if there's no hwmon
+/// sysfs interface.
+#[cfg(target_os = "linux")]
+pub fn get_temperature_data_sysfs(
+    temp_type: &TemperatureType, filter: &Option<Filter>,
+) -> Result<Option<Vec<TempHarvest>>> {
+    Ok(none_if_unavailable(LmSensorsSnapshot::from_sysfs())?
+        .map(|snapshot| snapshot.temperatures(temp_type, filter)))
+}
+
+/// Reports whether `sensors` can be run, its version, and how many devices
+/// lm_sensors finds. This runs `sensors --version` and then collects once, and
+/// has no other side effects.
+pub fn sensor_backend_status() -> BackendStatus {
+    backend_status(None)
+}
+
+fn backend_status(sensors_path: Option<&Path>) -> BackendStatus {
+    let version = run_sensors(
+        sensors_path.unwrap_or(Path::new(DEFAULT_SENSORS_PATH)),
+        &["--version"],
+        DEFAULT_SENSORS_TIMEOUT,
+    );
+
+    BackendStatus {
+        lmsensors_enabled: true,
+        sensors_available: version.is_ok(),
+        sensors_version: version
+            .ok()
+            .and_then(|output| parse_sensors_version(&output.stdout)),
+        device_count: LmSensorsSnapshot::collect(sensors_path, &[], DEFAULT_SENSORS_TIMEOUT)
+            .ok()
+            .map(|snapshot| snapshot.devices.len()),
+    }
+}
+
+/// Gets the version from the output of `sensors --version`, e.g. `3.6.0` from
+/// `sensors version 3.6.0 with libsensors version 3.6.0`.
+fn parse_sensors_version(output: &str) -> Option<String> {
+    output
+        .trim()
+        .strip_prefix("sensors version ")?
+        .split_whitespace()
+        .next()
+        .map(str::to_string)
+}
+
+/// Returns temperatures from lm_sensors grouped by device. See
+/// [`LmSensorsSnapshot::temperature_tree`].
+pub fn get_temperature_tree(
+    temp_type: &TemperatureType, filter: &Option<Filter>,
+) -> Result<Option<Vec<DeviceTemps>>> {
+    Ok(none_if_unavailable(LmSensorsSnapshot::new())?
+        .map(|snapshot| snapshot.temperature_tree(temp_type, filter)))
+}
+
+/// Sorts temperatures alphabetically by name. This is stable, so sensors with
+/// the same name keep their order.
+fn sort_temperatures_by_name(temperatures: &mut [TempHarvest]) {
+    temperatures.sort_by(|a, b| a.name.cmp(&b.name));
+}
+
+/// Returns the temperatures from already-collected `sensors -u` or `sensors -j`
+/// output, without running `sensors`.
+pub fn parse_temperature_data(
+    data: &str, temp_type: &TemperatureType, filter: &Option<Filter>,
+) -> Vec<TempHarvest> {
+    LmSensorsSnapshot::from_sensors_output(data).temperatures(temp_type, filter)
+}
+
+/// Returns fan speeds (in RPM) from lm_sensors. Stopped fans that report zero
+/// RPM are still included.
+pub fn get_fan_data(filter: &Option<Filter>) -> Result<Option<Vec<FanHarvest>>> {
+    Ok(none_if_unavailable(LmSensorsSnapshot::new())?.map(|snapshot| snapshot.fans(filter)))
+}
+
+/// Returns voltages from lm_sensors. Negative rails (e.g. -12V) keep their sign.
+pub fn get_voltage_data(filter: &Option<Filter>) -> Result<Option<Vec<VoltageHarvest>>> {
+    Ok(none_if_unavailable(LmSensorsSnapshot::new())?.map(|snapshot| snapshot.voltages(filter)))
+}
+
+/// Returns power draw (in watts) from lm_sensors, e.g. from RAPL or PSUs.
+pub fn get_power_data(filter: &Option<Filter>) -> Result<Option<Vec<PowerHarvest>>> {
+    Ok(none_if_unavailable(LmSensorsSnapshot::new())?.map(|snapshot| snapshot.powers(filter)))
+}
+
+/// Returns currents (in amps) from lm_sensors.
+pub fn get_current_data(filter: &Option<Filter>) -> Result<Option<Vec<CurrentHarvest>>> {
+    Ok(none_if_unavailable(LmSensorsSnapshot::new())?.map(|snapshot| snapshot.currents(filter)))
+}
+
+/// Returns relative humidity (as a percentage) from lm_sensors, for the few
+/// environmental sensor chips that report it.
+pub fn get_humidity_data(filter: &Option<Filter>) -> Result<Option<Vec<HumidityHarvest>>> {
+    Ok(none_if_unavailable(LmSensorsSnapshot::new())?.map(|snapshot| snapshot.humidities(filter)))
+}
+
+/// Returns fan PWM duty cycles (as percentages) from lm_sensors.
+pub fn get_pwm_data(filter: &Option<Filter>) -> Result<Option<Vec<PwmHarvest>>> {
+    Ok(none_if_unavailable(LmSensorsSnapshot::new())?.map(|snapshot| snapshot.pwms(filter)))
+}
+
+/// Returns chassis intrusion sensors from lm_sensors, and whether each has been
+/// triggered.
+pub fn get_intrusion_data(filter: &Option<Filter>) -> Result<Option<Vec<IntrusionHarvest>>> {
+    Ok(none_if_unavailable(LmSensorsSnapshot::new())?.map(|snapshot| snapshot.intrusions(filter)))
+}
+
+/// Returns the names of every sensor from lm_sensors, e.g. for picking which
+/// sensors to include or exclude in a filter. See
+/// [`LmSensorsSnapshot::sensor_names`].
+pub fn list_sensor_names(filter: &Option<Filter>) -> Result<Vec<SensorName>> {
+    Ok(LmSensorsSnapshot::new()?.sensor_names(filter))
+}
+
+/// Returns a readable dump of everything lm_sensors reported and how each
+/// sensor was classified, for use in bug reports.
+pub fn debug_dump_sensors() -> String {
+    match get_lm_sensor_data(None, &[], DEFAULT_SENSORS_TIMEOUT) {
+        Ok((devices, None)) => format_devices(&devices),
+        Ok((devices, Some(warning))) => format!("Warning: {warning}\n{}", format_devices(&devices)),
+        Err(err) => format!("Failed to collect sensor data: {err:#}"),
+    }
+}
+
+fn format_devices(devices: &[LmSensorsDevice]) -> String {
+    let mut dump = String::new();
+
+    for device in devices {
+        let _ = writeln!(dump, "{} (adapter: {:?})", device.name, device.adapter);
+
+        for sensor in &device.sensors {
+            let value = sensor
+                .value
+                .map_or_else(|| "N/A".to_string(), |value| value.to_string());
+
+            let _ = writeln!(
+                dump,
+                "  {:?} [{:?}] = {} (high: {:?}, crit: {:?}){}",
+                sensor.name,
+                sensor.sensor_type,
+                value,
+                sensor.high,
+                sensor.crit,
+                if sensor.alarm { " ALARM" } else { "" }
+            );
+        }
+    }
+
+    dump
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use regex::Regex;
+
+    use super::*;
+    use crate::data_collection::temperature::PLAUSIBLE_CELSIUS_RANGE;
+
+    /// Writes `script` to an executable `sensors` in `dir`, to stand in for the
+    /// real one.
+    #[cfg(target_os = "linux")]
+    pub(super) fn stub_sensors(dir: &Path, script: &str) -> std::path::PathBuf {
+        use std::{fs, os::unix::fs::PermissionsExt};
+
+        let sensors_path = dir.join("sensors");
+        fs::write(&sensors_path, script).unwrap();
+        fs::set_permissions(&sensors_path, fs::Permissions::from_mode(0o755)).unwrap();
+        sensors_path
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_output_with_timeout() {
+        let output =
+            output_with_timeout(Command::new("echo").arg("hello"), Duration::from_secs(5)).unwrap();
+        assert!(output.status.success());
+        assert_eq!(output.stdout, b"hello\n");
+
+        let start = Instant::now();
+        let result = output_with_timeout(Command::new("sleep").arg("5"), Duration::from_millis(50));
+        assert!(result.is_err());
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_with_deadline() {
+        assert_eq!(with_deadline(Duration::from_secs(5), || 1).unwrap(), 1);
+
+        let start = Instant::now();
+        let result = with_deadline(Duration::from_millis(50), || {
+            thread::sleep(Duration::from_secs(5));
+        });
+        assert!(result.is_err());
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_sensors_path() {
+        // A stub `sensors` that doesn't support `-j`, so `-u` output is used.
+        let dir = tempfile::tempdir().unwrap();
+        let sensors_path = stub_sensors(
+            dir.path(),
+            indoc! {r#"
+                #!/bin/sh
+                [ "$1" = "-u" ] || exit 1
+                printf 'k10temp-pci-00c3\nAdapter: PCI adapter\nTctl:\n  temp1_input: 45.000\n'
+            "#},
+        );
+
+        let snapshot =
+            LmSensorsSnapshot::with_sensors_path(&sensors_path, DEFAULT_SENSORS_TIMEOUT).unwrap();
+        let temperatures = snapshot.temperatures(&TemperatureType::Celsius, &None);
+        assert_eq!(temperatures.len(), 1);
+        assert_eq!(temperatures[0].name, "CPU: Tctl");
+        assert_eq!(temperatures[0].temperature, Some(45.0));
+
+        let missing = LmSensorsSnapshot::with_sensors_path(
+            &dir.path().join("missing"),
+            DEFAULT_SENSORS_TIMEOUT,
+        );
+        assert!(matches!(none_if_unavailable(missing), Ok(None)));
+
+        // A `sensors` that exists but can't be run is an error, not "not installed".
+        let not_executable = dir.path().join("not_executable");
+        std::fs::write(&not_executable, "").unwrap();
+        let not_executable =
+            LmSensorsSnapshot::with_sensors_path(&not_executable, DEFAULT_SENSORS_TIMEOUT);
+        assert!(none_if_unavailable(not_executable).is_err());
+    }
+
+    #[test]
+    fn test_unsupported_platform() {
+        assert!(matches!(
+            none_if_unavailable::<()>(Err(unsupported_platform())),
+            Ok(None)
+        ));
+        assert!(none_if_unavailable::<()>(Err(anyhow::anyhow!("sensors failed"))).is_err());
+
+        if cfg!(target_os = "windows") {
+            assert!(matches!(get_fan_data(&None), Ok(None)));
+            assert!(matches!(
+                get_temperature_tree(&TemperatureType::Celsius, &None),
+                Ok(None)
+            ));
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_partial_failure() {
+        // A stub `sensors` that reads one chip, but fails on another.
+        let dir = tempfile::tempdir().unwrap();
+        let sensors_path = stub_sensors(
+            dir.path(),
+            indoc! {r#"
+                #!/bin/sh
+                [ "$1" = "-u" ] || exit 1
+                printf 'k10temp-pci-00c3\nAdapter: PCI adapter\nTctl:\n  temp1_input: 45.000\n'
+                exit 1
+            "#},
+        );
+
+        let snapshot =
+            LmSensorsSnapshot::with_sensors_path(&sensors_path, DEFAULT_SENSORS_TIMEOUT).unwrap();
+        let temperatures = snapshot.temperatures(&TemperatureType::Celsius, &None);
+        assert_eq!(temperatures.len(), 1);
+        assert_eq!(temperatures[0].temperature, Some(45.0));
+        assert!(snapshot
+            .warning()
+            .is_some_and(|warning| warning.contains("exit status: 1")));
+
+        // The warning is passed on to data collection through the bundle.
+        assert_eq!(
+            snapshot.bundle(&None).warning.as_deref(),
+            snapshot.warning()
+        );
+
+        // Runs that succeed don't have a warning. This is a new script rather than
+        // a rewrite of the old one, as that could still be open for execution.
+        let dir = tempfile::tempdir().unwrap();
+        let sensors_path = stub_sensors(dir.path(), "#!/bin/sh\n");
+        let snapshot =
+            LmSensorsSnapshot::with_sensors_path(&sensors_path, DEFAULT_SENSORS_TIMEOUT).unwrap();
+        assert_eq!(snapshot.warning(), None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_json_support_probed_once() {
+        // A stub `sensors` that doesn't support `-j`, and logs how it's run.
+        let dir = tempfile::tempdir().unwrap();
+        let sensors_path = stub_sensors(
+            dir.path(),
+            indoc! {r#"
+                #!/bin/sh
+                echo "$1" >> "$(dirname "$0")/log"
+                [ "$1" = "-u" ] || exit 1
+                printf 'k10temp-pci-00c3\nAdapter: PCI adapter\nTctl:\n  temp1_input: 45.000\n'
+            "#},
+        );
+
+        // The failed `-j` run's warning is kept on the first run.
+        let snapshot =
+            LmSensorsSnapshot::with_sensors_path(&sensors_path, DEFAULT_SENSORS_TIMEOUT).unwrap();
+        assert_eq!(
+            snapshot
+                .temperatures(&TemperatureType::Celsius, &None)
+                .len(),
+            1
+        );
+        assert!(snapshot.warning().is_some());
+
+        let snapshot =
+            LmSensorsSnapshot::with_sensors_path(&sensors_path, DEFAULT_SENSORS_TIMEOUT).unwrap();
+        assert_eq!(
+            snapshot
+                .temperatures(&TemperatureType::Celsius, &None)
+                .len(),
+            1
+        );
+        assert_eq!(snapshot.warning(), None);
+
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("log")).unwrap(),
+            "-j\n-u\n-u\n"
+        );
+    }
+
+    #[test]
+    fn test_sensors_args() {
+        let chips = ["coretemp-isa-0000".to_string(), "nct6798-*".to_string()];
+        assert_eq!(
+            sensors_args("-u", &chips).unwrap(),
+            ["-u", "coretemp-isa-0000", "nct6798-*"]
+        );
+        assert_eq!(sensors_args("-j", &[]).unwrap(), ["-j"]);
+
+        for chip in [
+            "",
+            "-c/etc/evil.conf",
+            "--set",
+            "k10temp pci",
+            "a;b",
+            "../x",
+        ] {
+            assert!(
+                sensors_args("-u", &[chip.to_string()]).is_err(),
+                "{chip:?} should be rejected"
+            );
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_backend_status() {
+        let dir = tempfile::tempdir().unwrap();
+        let sensors_path = stub_sensors(
+            dir.path(),
+            indoc! {r#"
+                #!/bin/sh
+                case "$1" in
+                    --version) echo 'sensors version 3.6.0 with libsensors version 3.6.0' ;;
+                    -u) printf 'k10temp-pci-00c3\nAdapter: PCI adapter\nTctl:\n  temp1_input: 45.000\n' ;;
+                    *) exit 1 ;;
+                esac
+            "#},
+        );
+
+        assert_eq!(
+            backend_status(Some(&sensors_path)),
+            BackendStatus {
+                lmsensors_enabled: true,
+                sensors_available: true,
+                sensors_version: Some("3.6.0".to_string()),
+                device_count: Some(1),
+            }
+        );
+
+        let status = backend_status(Some(&dir.path().join("missing")));
+        assert!(!status.sensors_available);
+        assert_eq!(status.sensors_version, None);
+        assert_eq!(status.device_count, None);
+
+        assert_eq!(parse_sensors_version("unexpected"), None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_invalid_utf8_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let sensors_path = stub_sensors(
+            dir.path(),
+            indoc! {r#"
+                #!/bin/sh
+                [ "$1" = "-u" ] || exit 1
+                printf 'k10temp-pci-00c3\nAdapter: PCI adapter\nT\377ctl:\n  temp1_input: 45.000\n'
+            "#},
+        );
+
+        let snapshot =
+            LmSensorsSnapshot::with_sensors_path(&sensors_path, DEFAULT_SENSORS_TIMEOUT).unwrap();
+        let temperatures = snapshot.temperatures(&TemperatureType::Celsius, &None);
+        assert_eq!(temperatures.len(), 1);
+        assert_eq!(temperatures[0].name, "CPU: T\u{fffd}ctl");
+        assert_eq!(temperatures[0].temperature, Some(45.0));
+    }
+
+    #[test]
+    fn test_parse_thresholds() {
+        let data = indoc! {"
+            k10temp-pci-00c3
+            Adapter: PCI adapter
+            Tctl:
+              temp1_max: 70.000
+              temp1_crit: 95.000
+              temp1_input: 45.125
+            Tccd1:
+              temp3_input: 40.000
+        "};
+
+        let devices = parse_lm_sensors_data(data);
+        assert_eq!(devices.len(), 1);
+
+        let sensors = &devices[0].sensors;
+        assert_eq!(sensors.len(), 2);
+
+        assert_eq!(sensors[0].name, "Tctl");
+        assert_eq!(sensors[0].value, Some(45.125));
+        assert_eq!(sensors[0].high, Some(70.0));
+        assert_eq!(sensors[0].crit, Some(95.0));
+
+        assert_eq!(sensors[1].name, "Tccd1");
+        assert_eq!(sensors[1].high, None);
+        assert_eq!(sensors[1].crit, None);
+    }
+
+    #[test]
+    fn test_decimal_comma() {
+        assert_eq!(parse_lm_sensors_value("54,5"), Some(54.5));
+        assert_eq!(parse_lm_sensors_value("-5,5"), Some(-5.5));
+        assert_eq!(parse_lm_sensors_value("1,21"), Some(1.21));
+        assert_eq!(parse_lm_sensors_value("1234,000"), Some(1234.0));
+
+        // Something that could be a thousands separator isn't parsed, whatever
+        // the type of sensor, unless the part before the comma rules that out.
+        assert_eq!(parse_lm_sensors_value("1,234"), None);
+        assert_eq!(parse_lm_sensors_value("1,234"), None);
+        assert_eq!(parse_lm_sensors_value("54,125"), None);
+        assert_eq!(parse_lm_sensors_value("-54,125"), None);
+        assert_eq!(parse_lm_sensors_value("0,500"), Some(0.5));
+        assert_eq!(parse_lm_sensors_value("0,912"), Some(0.912));
+        assert_eq!(parse_lm_sensors_value("1200,000"), Some(1200.0));
+
+        // Anything else with a comma is left alone, and so doesn't parse.
+        assert_eq!(parse_lm_sensors_value("1,234,567"), None);
+        assert_eq!(parse_lm_sensors_value("1,234.000"), None);
+        assert_eq!(parse_lm_sensors_value(",5"), None);
+        assert_eq!(parse_lm_sensors_value("54,"), None);
+
+        assert_eq!(parse_lm_sensors_value("54.125"), Some(54.125));
+    }
+
+    #[test]
+    fn test_scientific_notation() {
+        let data = indoc! {"
+            nct6798-isa-0290
+            Adapter: ISA adapter
+            in0:
+              in0_input: 1.234e-03
+            in1:
+              in1_input: -0.00
+            in2:
+              in2_input: 0.00
+            SYSTIN:
+              temp1_input: 4.5E+01
+              temp1_max: 8.0e1
+            CPUTIN:
+              temp2_input: 6.5535e+04
+            AUXTIN0:
+              temp3_input: -0.000
+        "};
+        let snapshot = LmSensorsSnapshot::from_sensors_output(data);
+
+        let voltages = snapshot
+            .voltages(&None)
+            .into_iter()
+            .map(|voltage| voltage.volts)
+            .collect::<Vec<_>>();
+        assert_eq!(voltages, [Some(0.001234), Some(0.0), Some(0.0)]);
+        assert!(voltages
+            .iter()
+            .flatten()
+            .all(|volts| volts.is_sign_positive()));
+
+        // Exponential values still go through the plausibility check.
+        let temperatures = snapshot
+            .with_valid_range(PLAUSIBLE_CELSIUS_RANGE)
+            .temperatures(&TemperatureType::Celsius, &None);
+        assert_eq!(temperatures[0].temperature, Some(45.0));
+        assert_eq!(temperatures[0].high, Some(80.0));
+        assert_eq!(temperatures[1].temperature, None);
+        assert_eq!(temperatures[2].temperature, Some(0.0));
+        assert!(temperatures[2].temperature.unwrap().is_sign_positive());
+    }
+
+    #[test]
+    fn test_parse_alarms() {
+        let data = indoc! {"
+            nct6798-isa-0290
+            Adapter: ISA adapter
+            SYSTIN:
+              temp1_input: 30.000
+              temp1_alarm: 1.000
+            CPUTIN:
+              temp2_input: 38.500
+              temp2_crit_alarm: 1.000
+            AUXTIN0:
+              temp10_input: 35.000
+              temp1_alarm: 1.000
+              temp10_alarm: 0.000
+            fan1:
+              fan1_input: 0.000
+              fan1_alarm: 1.000
+            fan2:
+              fan2_input: 1024.000
+        "};
+
+        let alarms = parse_lm_sensors_data(data)[0]
+            .sensors
+            .iter()
+            .map(|sensor| sensor.alarm)
+            .collect::<Vec<_>>();
+        assert_eq!(alarms, [true, true, false, true, false]);
+
+        let snapshot = LmSensorsSnapshot::from_sensors_output(data);
+        let fans = snapshot.fans(&None);
+        assert!(fans[0].alarm);
+        assert!(!fans[1].alarm);
+        assert!(snapshot.temperatures(&TemperatureType::Celsius, &None)[0].alarm);
+    }
+
+    #[test]
+    fn test_input_not_first_subfield() {
+        let data = indoc! {"
+            nct6798-isa-0290
+            Adapter: ISA adapter
+            CPUTIN:
+              temp2_crit: 100.000
+              temp2_crit_alarm: 0.000
+              temp2_input: 38.500
+            fan2:
+              fan2_alarm: 0.000
+              fan2_min: 0.000
+              fan2_input: 1024.000
+            in0:
+              in0_min: 0.000
+              in0_max: 1.744
+              in0_input: 0.296
+        "};
+
+        let devices = parse_lm_sensors_data(data);
+        assert_eq!(devices.len(), 1);
+
+        let sensors = &devices[0].sensors;
+        assert_eq!(sensors.len(), 3);
+
+        assert_eq!(sensors[0].name, "CPUTIN");
+        assert_eq!(sensors[0].value, Some(38.5));
+        assert_eq!(sensors[0].sensor_type, LmSensorsSensorType::Temp);
+
+        assert_eq!(sensors[1].name, "fan2");
+        assert_eq!(sensors[1].value, Some(1024.0));
+        assert_eq!(sensors[1].sensor_type, LmSensorsSensorType::Fan);
+
+        assert_eq!(sensors[2].name, "in0");
+        assert_eq!(sensors[2].value, Some(0.296));
+        assert_eq!(sensors[2].sensor_type, LmSensorsSensorType::Voltage);
+    }
+
+    #[test]
+    fn test_hyphenated_sensor_names() {
+        let data = indoc! {"
+            k10temp-pci-00c3
+            Adapter: PCI adapter
+            Tctl:
+              temp1_input: 45.000
+            Core-0-Tctl:
+              temp2_input: 46.000
+              temp2_max: 70.000
+
+            nvme-pci-0100
+            Adapter: PCI adapter
+            Composite:
+              temp1_input: 30.850
+        "};
+
+        let devices = parse_lm_sensors_data(data);
+        assert_eq!(devices.len(), 2);
+
+        assert_eq!(devices[0].name, "k10temp-pci-00c3");
+        assert_eq!(devices[0].sensors.len(), 2);
+        assert_eq!(devices[0].sensors[1].name, "Core-0-Tctl");
+        assert_eq!(devices[0].sensors[1].value, Some(46.0));
+        assert_eq!(devices[0].sensors[1].high, Some(70.0));
+
+        assert_eq!(devices[1].name, "nvme-pci-0100");
+        assert_eq!(devices[1].adapter, "PCI adapter");
+        assert_eq!(devices[1].sensors.len(), 1);
+        assert_eq!(devices[1].sensors[0].name, "Composite");
+
+        assert!(is_device_header("iwlwifi_1-virtual-0"));
+        assert!(!is_device_header("Core-0-Tctl:"));
+        assert!(!is_device_header("  Core-0-Tctl"));
+    }
+
+    #[test]
+    fn test_translated_adapter() {
+        let data = indoc! {"
+            k10temp-pci-00c3
+            Adaptateur : Interface PCI
+            Tctl:
+              temp1_input: 40.000
+
+            nvme-pci-0100
+            Adapter :PCI adapter
+            Composite:
+              temp1_input: 35.000
+
+            acpitz-acpi-0
+            temp1:
+              temp1_input: 27.800
+        "};
+
+        let devices = parse_lm_sensors_data(data);
+        assert_eq!(devices.len(), 3);
+        assert_eq!(devices[0].adapter, "Interface PCI");
+        assert_eq!(devices[0].sensors[0].name, "Tctl");
+        assert_eq!(devices[1].adapter, "PCI adapter");
+        assert_eq!(devices[2].adapter, "");
+        assert_eq!(devices[2].sensors[0].name, "temp1");
+    }
+
+    #[test]
+    fn test_missing_adapter() {
+        let data = indoc! {"
+            virtual_temp-virtual-0
+            temp1:
+              temp1_input: 42.000
+
+            nvme-pci-0100
+            Adapter: PCI adapter
+            Composite:
+              temp1_input: 30.850
+        "};
+
+        let devices = parse_lm_sensors_data(data);
+        assert_eq!(devices.len(), 2);
+
+        assert_eq!(devices[0].name, "virtual_temp-virtual-0");
+        assert_eq!(devices[0].adapter, "");
+        assert_eq!(devices[0].sensors.len(), 1);
+        assert_eq!(devices[0].sensors[0].name, "temp1");
+        assert_eq!(devices[0].sensors[0].value, Some(42.0));
+
+        assert_eq!(devices[1].name, "nvme-pci-0100");
+        assert_eq!(devices[1].adapter, "PCI adapter");
+
+        let data = indoc! {"
+            virtual_temp-virtual-0
+            nvme-pci-0100
+            Adapter: PCI adapter
+            Composite:
+              temp1_input: 30.850
+        "};
+
+        let devices = parse_lm_sensors_data(data);
+        assert_eq!(devices.len(), 2);
+
+        assert_eq!(devices[0].name, "virtual_temp-virtual-0");
+        assert!(devices[0].sensors.is_empty());
+
+        assert_eq!(devices[1].name, "nvme-pci-0100");
+        assert_eq!(devices[1].adapter, "PCI adapter");
+        assert_eq!(devices[1].sensors.len(), 1);
+    }
+
+    #[test]
+    fn test_no_adapter_lines() {
+        // Output from `sensors -A -u`, which leaves out every adapter line.
+        let data = indoc! {"
+            k10temp-pci-00c3
+            Tctl:
+              temp1_input: 45.000
+            Tccd1:
+              temp3_input: 40.000
+
+            nvme-pci-0100
+            Composite:
+              temp1_input: 30.850
+              temp1_max: 84.850
+
+            nct6798-isa-0290
+            in0:
+              in0_input: 0.296
+            fan1:
+              fan1_input: 1200.000
+        "};
+
+        let devices = parse_lm_sensors_data(data);
+        assert_eq!(devices.len(), 3);
+        assert!(devices.iter().all(|device| device.adapter.is_empty()));
+
+        assert_eq!(devices[0].name, "k10temp-pci-00c3");
+        assert_eq!(devices[0].sensors.len(), 2);
+        assert_eq!(devices[0].sensors[0].name, "Tctl");
+        assert_eq!(devices[0].sensors[0].value, Some(45.0));
+        assert_eq!(devices[0].sensors[1].name, "Tccd1");
+
+        assert_eq!(devices[1].name, "nvme-pci-0100");
+        assert_eq!(devices[1].sensors.len(), 1);
+        assert_eq!(devices[1].sensors[0].name, "Composite");
+        assert_eq!(devices[1].sensors[0].high, Some(84.85));
+
+        assert_eq!(devices[2].name, "nct6798-isa-0290");
+        assert_eq!(devices[2].sensors.len(), 2);
+        assert_eq!(devices[2].sensors[1].name, "fan1");
+        assert_eq!(devices[2].sensors[1].value, Some(1200.0));
+    }
+
+    #[test]
+    fn test_subfeature_errors() {
+        let data = indoc! {"
+            lm75-i2c-1-48
+            Adapter: SMBus I801 adapter at f000
+            temp1:
+            ERROR: Can't get value of subfeature temp1_input: Can't read
+              temp1_max: 80.000
+              temp1_max_hyst: 75.000
+            temp2:
+              temp2_input: 41.000
+        "};
+
+        let devices = parse_lm_sensors_data(data);
+        assert_eq!(devices.len(), 1);
+        assert_eq!(devices[0].sensors.len(), 2);
+
+        assert_eq!(devices[0].sensors[0].name, "temp1");
+        assert_eq!(devices[0].sensors[0].value, None);
+        assert_eq!(devices[0].sensors[0].high, Some(80.0));
+        assert_eq!(devices[0].sensors[1].name, "temp2");
+        assert_eq!(devices[0].sensors[1].value, Some(41.0));
+
+        assert_eq!(
+            parse_subfeature_error("ERROR: Can't get value of subfeature in0_input: Can't read"),
+            Some("in0_input")
+        );
+        assert_eq!(parse_subfeature_error("ERROR: Something else"), None);
+        assert_eq!(parse_subfeature_error("  temp1_input: 41.000"), None);
+    }
+
+    #[test]
+    fn test_invalid_values() {
+        let data = indoc! {"
+            acpitz-acpi-0
+            Adapter: ACPI interface
+            temp1:
+              temp1_input: 127000.000
+            temp2:
+              temp2_input: N/A
+            temp3:
+              temp3_input: 27.800
+            fan1:
+              fan1_input: NaN
+        "};
+
+        let devices = parse_lm_sensors_data(data);
+        let sensors = &devices[0].sensors;
+        assert_eq!(sensors.len(), 4);
+
+        // Implausible temperatures are still parsed, and only dropped if asked to.
+        assert_eq!(sensors[0].value, Some(127000.0));
+        assert_eq!(sensors[1].value, None);
+        assert_eq!(sensors[2].value, Some(27.8));
+        assert_eq!(sensors[3].value, None);
+
+        // Fans without a reading are kept, but aren't present, the same as with
+        // `fans`.
+        let snapshot = LmSensorsSnapshot::from_sensors_output(data);
+        let bundle = snapshot.bundle(&None);
+        assert_eq!(bundle.temperatures.len(), 3);
+        assert_eq!(bundle.temperatures[0].temperature, Some(127000.0));
+        assert_eq!(bundle.fans.len(), 1);
+        assert!(!bundle.fans[0].present);
+        assert_eq!(snapshot.fans(&None).len(), 1);
+
+        let bundle = snapshot
+            .with_valid_range(PLAUSIBLE_CELSIUS_RANGE)
+            .bundle(&None);
+        assert_eq!(bundle.temperatures.len(), 3);
+        assert_eq!(bundle.temperatures[0].temperature, None);
+        assert_eq!(bundle.temperatures[2].temperature, Some(27.8));
+    }
+
+    #[test]
+    fn test_sensor_types() {
+        assert_eq!(
+            parse_lm_sensors_sensor_type("temp1_input"),
+            LmSensorsSensorType::Temp
+        );
+        assert_eq!(
+            parse_lm_sensors_sensor_type("fan1_input"),
+            LmSensorsSensorType::Fan
+        );
+        assert_eq!(
+            parse_lm_sensors_sensor_type("in0_input"),
+            LmSensorsSensorType::Voltage
+        );
+        assert_eq!(
+            parse_lm_sensors_sensor_type("power1_input"),
+            LmSensorsSensorType::Power
+        );
+        assert_eq!(
+            parse_lm_sensors_sensor_type("curr1_input"),
+            LmSensorsSensorType::Current
+        );
+        assert_eq!(
+            parse_lm_sensors_sensor_type("humidity1_input"),
+            LmSensorsSensorType::Humidity
+        );
+        assert_eq!(
+            parse_lm_sensors_sensor_type("intrusion0_alarm"),
+            LmSensorsSensorType::Intrusion
+        );
+        assert_eq!(
+            parse_lm_sensors_sensor_type("pwm1"),
+            LmSensorsSensorType::Pwm
+        );
+    }
+
+    #[test]
+    fn test_fan_divisor() {
+        let data = indoc! {"
+            it8792-isa-0a60
+            Adapter: ISA adapter
+            fan1:
+              fan1_input: 1200.000
+              fan1_min: 0.000
+              fan1_div: 8.000
+            fan2:
+              fan2_input: 900.000
+        "};
+
+        // Known drivers already account for the divisor, so it's only captured.
+        let fans = LmSensorsSnapshot::from_sensors_output(data).fans(&None);
+        assert_eq!(fans[0].rpm, 1200.0);
+        assert_eq!(fans[0].divisor, Some(8.0));
+        assert_eq!(fans[1].divisor, None);
+
+        let drivers = ["oldchip"];
+        assert_eq!(
+            scale_fan_rpm("oldchip-isa-0290", 1200.0, Some(8.0), &drivers),
+            150.0
+        );
+        assert_eq!(
+            scale_fan_rpm("oldchip-isa-0290", 1200.0, None, &drivers),
+            1200.0
+        );
+        assert_eq!(
+            scale_fan_rpm("oldchip-isa-0290", 1200.0, Some(0.0), &drivers),
+            1200.0
+        );
+        assert_eq!(
+            scale_fan_rpm("it8792-isa-0a60", 1200.0, Some(8.0), &drivers),
+            1200.0
+        );
+    }
+
+    #[test]
+    fn test_fan_speed_fraction() {
+        let data = indoc! {"
+            nct6798-isa-0290
+            Adapter: ISA adapter
+            fan1:
+              fan1_input: 1200.000
+              fan1_min: 400.000
+              fan1_max: 2400.000
+            fan2:
+              fan2_input: 900.000
+              fan2_max: 1800.000
+            fan3:
+              fan3_input: 900.000
+              fan3_min: 300.000
+            fan4:
+              fan4_input: 3000.000
+              fan4_max: 2400.000
+            fan5:
+              fan5_fault: 1.000
+              fan5_max: 2400.000
+        "};
+
+        let fans = LmSensorsSnapshot::from_sensors_output(data).fans(&None);
+        assert_eq!(fans[0].min, Some(400.0));
+        assert_eq!(fans[0].max, Some(2400.0));
+
+        let fractions = fans
+            .iter()
+            .map(FanHarvest::speed_fraction)
+            .collect::<Vec<_>>();
+        assert_eq!(fractions, [Some(0.4), Some(0.5), None, Some(1.0), None]);
+
+        let fan = |min, max| FanHarvest {
+            rpm: 1000.0,
+            present: true,
+            min,
+            max,
+            ..Default::default()
+        };
+        assert_eq!(fan(Some(1000.0), Some(1000.0)).speed_fraction(), None);
+        assert_eq!(fan(Some(1500.0), Some(2000.0)).speed_fraction(), Some(0.0));
+    }
+
+    #[test]
+    fn test_noise_tier() {
+        assert_eq!(noise_tier(0.0), NoiseTier::Quiet);
+        assert_eq!(noise_tier(599.9), NoiseTier::Quiet);
+        assert_eq!(noise_tier(600.0), NoiseTier::Normal);
+        assert_eq!(noise_tier(1499.9), NoiseTier::Normal);
+        assert_eq!(noise_tier(1500.0), NoiseTier::Loud);
+
+        let thresholds = NoiseThresholds {
+            quiet_below: 1000.0,
+            normal_below: 2000.0,
+        };
+        assert_eq!(thresholds.tier(999.0), NoiseTier::Quiet);
+        assert_eq!(thresholds.tier(1000.0), NoiseTier::Normal);
+        assert_eq!(thresholds.tier(2000.0), NoiseTier::Loud);
+
+        let fan = FanHarvest {
+            rpm: 1200.0,
+            present: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            fan.noise_tier(&NoiseThresholds::default()),
+            NoiseTier::Normal
+        );
+        assert_eq!(fan.noise_tier(&thresholds), NoiseTier::Normal);
+    }
+
+    #[test]
+    fn test_fan_presence() {
+        let data = indoc! {"
+            nct6798-isa-0290
+            Adapter: ISA adapter
+            fan1:
+              fan1_input: 1200.000
+            fan2:
+              fan2_input: 0.000
+            fan3:
+              fan3_input: 0.000
+              fan3_fault: 1.000
+            fan4:
+              fan4_fault: 1.000
+            fan5:
+              fan5_input: 0.000
+              fan5_fault: 0.000
+        "};
+
+        let snapshot = LmSensorsSnapshot::from_sensors_output(data);
+        let presence = |fans: &[FanHarvest]| {
+            fans.iter()
+                .map(|fan| (fan.rpm, fan.present))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            presence(&snapshot.fans(&None)),
+            vec![
+                (1200.0, true),
+                (0.0, true),
+                (0.0, false),
+                (0.0, false),
+                (0.0, true)
+            ]
+        );
+
+        // The bundle has the same fans, including the fault-only one.
+        assert_eq!(
+            presence(&snapshot.bundle(&None).fans),
+            presence(&snapshot.fans(&None))
+        );
+    }
+
+    #[test]
+    fn test_hide_unpopulated() {
+        let data = indoc! {"
+            nct6798-isa-0290
+            Adapter: ISA adapter
+            in0:
+              in0_input: 0.000
+            3VCC:
+              in1_input: 3.296
+            fan1:
+              fan1_input: 1200.000
+            fan2:
+              fan2_input: 0.000
+            fan3:
+              fan3_input: 0.000
+              fan3_fault: 1.000
+            SYSTIN:
+              temp1_input: 0.000
+        "};
+        let names = |bundle: &SensorBundle| {
+            (
+                bundle
+                    .voltages
+                    .iter()
+                    .map(|voltage| voltage.name.clone())
+                    .collect::<Vec<_>>(),
+                bundle
+                    .fans
+                    .iter()
+                    .map(|fan| fan.name.clone())
+                    .collect::<Vec<_>>(),
+            )
+        };
+
+        let snapshot = LmSensorsSnapshot::from_sensors_output(data);
+        let bundle = snapshot.bundle(&None);
+        assert_eq!(bundle.voltages.len(), 2);
+        assert_eq!(bundle.fans.len(), 3);
+
+        let snapshot = snapshot.with_hide_unpopulated(true);
+        let bundle = snapshot.bundle(&None);
+        let (voltages, fans) = names(&bundle);
+
+        // The 0V rail and the faulted fan are hidden, but a stopped fan isn't.
+        assert_eq!(voltages, ["nct6798: 3VCC"]);
+        assert_eq!(fans, ["nct6798: fan1", "nct6798: fan2"]);
+        assert_eq!(bundle.temperatures.len(), 1);
+        assert_eq!(snapshot.voltages(&None).len(), 1);
+    }
+
+    #[test]
+    fn test_pwm() {
+        let data = indoc! {"
+            nct6798-isa-0290
+            Adapter: ISA adapter
+            fan1:
+              fan1_input: 1034.000
+            pwm1:
+              pwm1: 0.000
+              pwm1_enable: 5.000
+            pwm2:
+              pwm2: 128.000
+              pwm2_enable: 5.000
+            pwm3:
+              pwm3: 255.000
+        "};
+
+        let snapshot = LmSensorsSnapshot::from_sensors_output(data);
+        let pwms = snapshot
+            .pwms(&None)
+            .into_iter()
+            .map(|pwm| (pwm.name, pwm.percent))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            pwms,
+            [
+                ("nct6798: pwm1".to_string(), 0.0),
+                ("nct6798: pwm2".to_string(), 128.0 / 255.0 * 100.0),
+                ("nct6798: pwm3".to_string(), 100.0),
+            ]
+        );
+        assert!((pwms[1].1 - 50.2).abs() < 0.01);
+
+        let bundle = snapshot.bundle(&None);
+        assert_eq!(bundle.fans.len(), 1);
+        assert_eq!(bundle.pwms.len(), 3);
+    }
+
+    #[test]
+    fn test_intrusion() {
+        let data = indoc! {"
+            nct6798-isa-0290
+            Adapter: ISA adapter
+            intrusion0:
+              intrusion0_alarm: 1.000
+              intrusion0_beep: 0.000
+            intrusion1:
+              intrusion1_alarm: 0.000
+        "};
+
+        let snapshot = LmSensorsSnapshot::from_sensors_output(data);
+        let intrusions = snapshot.intrusions(&None);
+        assert_eq!(intrusions.len(), 2);
+        assert_eq!(intrusions[0].name, "nct6798: intrusion0");
+        assert!(intrusions[0].triggered);
+        assert!(!intrusions[1].triggered);
+
+        let bundle = snapshot.bundle(&None);
+        assert_eq!(bundle.intrusions.len(), 2);
+        assert!(bundle.voltages.is_empty());
+    }
+
+    #[test]
+    fn test_format_devices() {
+        let data = indoc! {"
+            k10temp-pci-00c3
+            Adapter: PCI adapter
+            Tctl:
+              temp1_input: 45.000
+              temp1_max: 70.000
+            fan1:
+              fan1_input: 1200.000
+        "};
+
+        assert_eq!(
+            format_devices(&parse_lm_sensors_data(data)),
+            indoc! {r#"
+                k10temp-pci-00c3 (adapter: "PCI adapter")
+                  "Tctl" [Temp] = 45 (high: Some(70.0), crit: None)
+                  "fan1" [Fan] = 1200 (high: None, crit: None)
+            "#}
+        );
+    }
+
+    #[test]
+    fn test_friendly_name_rules() {
+        let rules = vec![
+            NameRule::substring("NCT6798", "MB"),
+            NameRule::regex("^nvme-pci-01", "SSD").unwrap(),
+            NameRule::substring("nvme", "Disk"),
+            NameRule::regex("temp", "Overridden").unwrap(),
+        ];
+
+        assert_eq!(
+            format_friendly_names("nct6798-isa-0290", "SYSTIN", &rules),
+            "MB: SYSTIN"
+        );
+        assert_eq!(
+            format_friendly_names("nvme-pci-0100", "Composite", &rules),
+            "SSD: Composite"
+        );
+        assert_eq!(
+            format_friendly_names("nvme-pci-0200", "Composite", &rules),
+            "Disk: Composite"
+        );
+
+        // Rules take priority over the built-in names, which come before the
+        // device name prefix.
+        assert_eq!(
+            format_friendly_names("k10temp-pci-00c3", "Tctl", &rules),
+            "Overridden: Tctl"
+        );
+        assert_eq!(
+            format_friendly_names("k10temp-pci-00c3", "Tctl", &rules[..1]),
+            "CPU: Tctl"
+        );
+        assert_eq!(
+            format_friendly_names("nct6798-isa-0290", "SYSTIN", &[]),
+            "nct6798: SYSTIN"
+        );
+    }
+
+    #[test]
+    fn test_calibration() {
+        let data = indoc! {"
+            k10temp-pci-00c3
+            Adapter: PCI adapter
+            Tctl:
+              temp1_input: 65.000
+              temp1_crit: 100.000
+            Tccd1:
+              temp3_input: 45.000
+        "};
+        let calibration = HashMap::from_iter([("k10temp-pci-00c3/Tctl".to_string(), 5.0)]);
+        let snapshot = LmSensorsSnapshot::from_sensors_output(data).with_calibration(calibration);
+
+        let temperatures = snapshot.temperatures(&TemperatureType::Celsius, &None);
+        assert_eq!(temperatures[0].temperature, Some(70.0));
+        assert_eq!(temperatures[0].crit, Some(100.0));
+        assert_eq!(temperatures[1].temperature, Some(45.0));
+
+        // The offset is applied in Celsius, before converting.
+        let temperatures = snapshot.temperatures(&TemperatureType::Fahrenheit, &None);
+        assert_eq!(temperatures[0].temperature, Some(158.0));
+        assert_eq!(temperatures[1].temperature, Some(113.0));
+
+        let bundle = snapshot.bundle(&None);
+        assert_eq!(bundle.temperatures[0].temperature, Some(70.0));
+    }
+
+    #[test]
+    fn test_raw_names() {
+        let data = indoc! {"
+            nct6798-isa-0290
+            Adapter: ISA adapter
+            temp2:
+              temp2_input: 35.000
+        "};
+        let snapshot = LmSensorsSnapshot::from_sensors_output(data);
+
+        let temperatures = snapshot.temperatures(&TemperatureType::Celsius, &None);
+        assert_eq!(temperatures[0].name, "nct6798: temp2");
+
+        let snapshot = snapshot.with_friendly_names(false);
+        let temperatures = snapshot.temperatures(&TemperatureType::Celsius, &None);
+        assert_eq!(temperatures[0].name, "nct6798-isa-0290 / temp2");
+        assert_eq!(temperatures[0].sensor_id, "nct6798-isa-0290/temp2");
+    }
+
+    #[test]
+    fn test_friendly_name_families() {
+        assert_eq!(
+            format_friendly_names("k10temp-pci-00c3", "Tctl", &[]),
+            "CPU: Tctl"
+        );
+        assert_eq!(
+            format_friendly_names("it8686-isa-0a40", "temp1", &[]),
+            "MB: temp1"
+        );
+
+        // Names that only contain a family name elsewhere aren't matched.
+        assert_eq!(
+            format_friendly_names("bk1080-isa-0000", "temp1", &[]),
+            "bk1080: temp1"
+        );
+        assert_eq!(
+            format_friendly_names("jc42-i2c-k10-18", "temp1", &[]),
+            "jc42: temp1"
+        );
+        assert_eq!(
+            format_friendly_names("wit86x-isa-0290", "temp1", &[]),
+            "wit86x: temp1"
+        );
+    }
+
+    #[test]
+    fn test_friendly_name_case() {
+        assert_eq!(
+            format_friendly_names("NVIDIA-pci-0100", "temp1", &[]),
+            "Gpu: temp1"
+        );
+        assert_eq!(
+            format_friendly_names("Kraken-hid-3-1", "Coolant", &[]),
+            "AIO: Coolant"
+        );
+    }
+
+    #[test]
+    fn test_temperatures() {
+        let data = indoc! {"
+            k10temp-pci-00c3
+            Adapter: PCI adapter
+            Tctl:
+              temp1_input: 50.000
+              temp1_max: 70.000
+              temp1_crit: 100.000
+            fan1:
+              fan1_input: 1200.000
+        "};
+
+        let snapshot = LmSensorsSnapshot::from_sensors_output(data);
+        let temperatures = snapshot.temperatures(&TemperatureType::Fahrenheit, &None);
+
+        assert_eq!(temperatures.len(), 1);
+        assert_eq!(temperatures[0].name, "CPU: Tctl");
+        assert_eq!(temperatures[0].temperature, Some(122.0));
+        assert_eq!(temperatures[0].high, Some(158.0));
+        assert_eq!(temperatures[0].crit, Some(212.0));
+        assert_eq!(temperatures[0].adapter.as_deref(), Some("PCI adapter"));
+        assert_eq!(temperatures[0].chip.as_deref(), Some("k10temp-pci-00c3"));
+        assert_eq!(temperatures[0].timestamp, Some(snapshot.timestamp));
+    }
+
+    #[test]
+    fn test_bundle() {
+        let data = indoc! {"
+            nct6798-isa-0290
+            Adapter: ISA adapter
+            in0:
+              in0_input: 0.296
+            fan1:
+              fan1_input: 1200.000
+            SYSTIN:
+              temp1_input: 30.000
+            PPT:
+              power1_input: 25.000
+            curr1:
+              curr1_input: 1.500
+            humidity1:
+              humidity1_input: 40.000
+        "};
+
+        let snapshot = LmSensorsSnapshot::from_sensors_output(data);
+        let bundle = snapshot.bundle(&None);
+
+        assert_eq!(bundle.temperatures.len(), 1);
+        assert_eq!(bundle.temperatures[0].name, "nct6798: SYSTIN");
+        assert_eq!(bundle.temperatures[0].temperature, Some(30.0));
+        assert_eq!(bundle.fans.len(), 1);
+        assert_eq!(bundle.fans[0].rpm, 1200.0);
+        assert_eq!(bundle.voltages.len(), 1);
+        assert_eq!(bundle.voltages[0].volts, Some(0.296));
+        assert_eq!(bundle.powers.len(), 1);
+        assert_eq!(bundle.powers[0].watts, Some(25.0));
+        assert_eq!(bundle.currents.len(), 1);
+        assert_eq!(bundle.currents[0].amps, 1.5);
+        assert_eq!(bundle.humidities.len(), 1);
+        assert_eq!(bundle.humidities[0].percent, 40.0);
+    }
+
+    #[test]
+    fn test_device_averages() {
+        let data = indoc! {"
+            k10temp-pci-00c3
+            Adapter: PCI adapter
+            Tctl:
+              temp1_input: 50.000
+            Tccd1:
+              temp3_input: 40.000
+            Tccd2:
+              temp4_input: N/A
+
+            nvme-pci-0100
+            Adapter: PCI adapter
+            Composite:
+              temp1_input: N/A
+
+            acpitz-acpi-0
+            Adapter: ACPI interface
+            temp1:
+              temp1_input: 27.500
+        "};
+        let bundle = LmSensorsSnapshot::from_sensors_output(data).bundle(&None);
+
+        assert_eq!(
+            device_averages(&bundle),
+            [
+                ("k10temp-pci-00c3".to_string(), 45.0),
+                ("acpitz-acpi-0".to_string(), 27.5),
+            ]
+        );
+        assert!(device_averages(&SensorBundle::default()).is_empty());
+    }
+
+    #[test]
+    fn test_sensor_counts() {
+        let data = indoc! {"
+            nct6798-isa-0290
+            Adapter: ISA adapter
+            in0:
+              in0_input: 0.296
+            in1:
+              in1_input: 1.024
+            in2:
+              in2_input: N/A
+            fan1:
+              fan1_input: 1200.000
+            SYSTIN:
+              temp1_input: 32.000
+            AUXTIN0:
+              temp3_input: N/A
+
+            amdgpu-pci-0c00
+            Adapter: PCI adapter
+            edge:
+              temp1_input: 45.000
+            PPT:
+              power1_input: 25.000
+            slowPPT:
+              power2_input: N/A
+        "};
+        let bundle = LmSensorsSnapshot::from_sensors_output(data).bundle(&None);
+
+        assert_eq!(
+            sensor_counts(&bundle),
+            SensorCounts {
+                temps: 3,
+                fans: 1,
+                voltages: 3,
+                powers: 2,
+            }
+        );
+        assert_eq!(
+            sensor_counts(&SensorBundle::default()),
+            SensorCounts::default()
+        );
+    }
+
+    #[test]
+    fn test_temperatures_as_f32_vec() {
+        let data = indoc! {"
+            nct6798-isa-0290
+            Adapter: ISA adapter
+            SYSTIN:
+              temp1_input: 32.000
+            AUXTIN0:
+              temp3_input: N/A
+            CPUTIN:
+              temp2_input: 40.000
+        "};
+        let bundle = LmSensorsSnapshot::from_sensors_output(data).bundle(&None);
+
+        let values = temperatures_as_f32_vec(&bundle, TemperatureType::Kelvin);
+        assert_eq!(values.len(), 3);
+        assert!((values[0] - 305.15).abs() < 0.001);
+        assert!(values[1].is_nan());
+        assert!((values[2] - 313.15).abs() < 0.001);
+
+        assert_eq!(
+            temperature_names(&bundle),
+            vec!["nct6798: SYSTIN", "nct6798: AUXTIN0", "nct6798: CPUTIN"]
+        );
+
+        // Readings are converted from the bundle's unit, not assumed to be Celsius.
+        let mut fahrenheit = bundle.clone();
+        fahrenheit.convert_temperatures(TemperatureType::Fahrenheit);
+        let values = temperatures_as_f32_vec(&fahrenheit, TemperatureType::Celsius);
+        assert!((values[0] - 32.0).abs() < 0.001);
+        assert!(values[1].is_nan());
+        assert!(
+            temperatures_as_f32_vec(&SensorBundle::default(), TemperatureType::Celsius).is_empty()
+        );
+    }
+
+    #[test]
+    fn test_bundle_diff() {
+        let before = indoc! {"
+            k10temp-pci-00c3
+            Adapter: PCI adapter
+            Tctl:
+              temp1_input: 45.000
+
+            nct6798-isa-0290
+            Adapter: ISA adapter
+            fan1:
+              fan1_input: 1200.000
+            in0:
+              in0_input: 1.024
+        "};
+        let after = indoc! {"
+            k10temp-pci-00c3
+            Adapter: PCI adapter
+            Tctl:
+              temp1_input: 52.500
+
+            nct6798-isa-0290
+            Adapter: ISA adapter
+            in0:
+              in0_input: 1.024
+
+            amdgpu-pci-0c00
+            Adapter: PCI adapter
+            edge:
+              temp1_input: 40.000
+        "};
+        let before = LmSensorsSnapshot::from_sensors_output(before).bundle(&None);
+        let after = LmSensorsSnapshot::from_sensors_output(after).bundle(&None);
+
+        let deltas = before.diff(&after);
+        assert_eq!(deltas.len(), 3);
+
+        assert_eq!(deltas[0].sensor_type, "temperature");
+        assert_eq!(deltas[0].id, "k10temp-pci-00c3/Tctl");
+        assert_eq!(deltas[0].change, SensorChange::Changed);
+        assert_eq!(deltas[0].old, Some(45.0));
+        assert_eq!(deltas[0].new, Some(52.5));
+        assert_eq!(deltas[0].delta(), Some(7.5));
+
+        assert_eq!(deltas[1].sensor_type, "fan");
+        assert_eq!(deltas[1].id, "nct6798-isa-0290/fan1");
+        assert_eq!(deltas[1].change, SensorChange::Removed);
+        assert_eq!(deltas[1].old, Some(1200.0));
+        assert_eq!(deltas[1].delta(), None);
+
+        assert_eq!(deltas[2].id, "amdgpu-pci-0c00/edge");
+        assert_eq!(deltas[2].change, SensorChange::Added);
+        assert_eq!(deltas[2].new, Some(40.0));
+
+        assert!(before.diff(&before).is_empty());
+
+        // Sensors are matched by id, so renaming them doesn't count as a change.
+        let mut renamed = before.clone();
+        renamed.fans[0].name = "CPU fan".to_string();
+        renamed.voltages[0].name = "Vcore".to_string();
+        assert!(before.diff(&renamed).is_empty());
+    }
+
+    #[test]
+    fn test_bundle_unit_conversion() {
+        let data = indoc! {"
+            k10temp-pci-00c3
+            Adapter: PCI adapter
+            Tctl:
+              temp1_input: 50.000
+              temp1_crit: 100.000
+        "};
+
+        let mut bundle = LmSensorsSnapshot::from_sensors_output(data).bundle(&None);
+        assert_eq!(bundle.unit, TemperatureType::Celsius);
+
+        bundle.convert_temperatures(TemperatureType::Fahrenheit);
+        assert_eq!(bundle.unit, TemperatureType::Fahrenheit);
+        assert_eq!(bundle.temperatures[0].temperature, Some(122.0));
+        assert_eq!(bundle.temperatures[0].crit, Some(212.0));
+        assert_eq!(
+            bundle.temperatures[0].in_unit(TemperatureType::Celsius),
+            Some(50.0)
+        );
+
+        // Converting to the same unit again is a no-op.
+        bundle.convert_temperatures(TemperatureType::Fahrenheit);
+        assert_eq!(bundle.temperatures[0].temperature, Some(122.0));
+
+        bundle.convert_temperatures(TemperatureType::Celsius);
+        assert_eq!(bundle.unit, TemperatureType::Celsius);
+        assert_eq!(bundle.temperatures[0].temperature, Some(50.0));
+        assert_eq!(bundle.temperatures[0].crit, Some(100.0));
+    }
+
+    #[test]
+    fn test_device_filter() {
+        let data = indoc! {"
+            acpitz-acpi-0
+            Adapter: ACPI interface
+            temp1:
+              temp1_input: 27.800
+            temp2:
+              temp2_input: 29.800
+
+            k10temp-pci-00c3
+            Adapter: PCI adapter
+            Tctl:
+              temp1_input: 45.000
+        "};
+
+        let snapshot = LmSensorsSnapshot::from_sensors_output(data);
+        let names = |filter: &Option<Filter>| {
+            snapshot
+                .temperatures(&TemperatureType::Celsius, filter)
+                .into_iter()
+                .map(|temp| temp.name)
+                .collect::<Vec<_>>()
+        };
+
+        let ignore_device = Some(Filter::new(true, vec![Regex::new("acpitz").unwrap()]));
+        assert_eq!(names(&ignore_device), vec!["CPU: Tctl"]);
+
+        let keep_device = Some(Filter::new(false, vec![Regex::new("acpitz").unwrap()]));
+        assert_eq!(names(&keep_device), vec!["acpitz: temp1", "acpitz: temp2"]);
+
+        let ignore_sensor = Some(Filter::new(true, vec![Regex::new("temp2").unwrap()]));
+        assert_eq!(names(&ignore_sensor), vec!["acpitz: temp1", "CPU: Tctl"]);
+    }
+
+    #[test]
+    fn test_friendly_name_collisions() {
+        let data = indoc! {"
+            nvme-pci-0100
+            Adapter: PCI adapter
+            Composite:
+              temp1_input: 30.850
+            Sensor 1:
+              temp2_input: 32.000
+
+            nvme-pci-0200
+            Adapter: PCI adapter
+            Composite:
+              temp1_input: 35.850
+
+            k10temp-pci-00c3
+            Adapter: PCI adapter
+            Tctl:
+              temp1_input: 45.000
+        "};
+
+        let snapshot = LmSensorsSnapshot::from_sensors_output(data);
+        let names = snapshot
+            .temperatures(&TemperatureType::Celsius, &None)
+            .into_iter()
+            .map(|temp| temp.name)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            names,
+            vec![
+                "Nvme: Composite (pci-0100)",
+                "Nvme: Sensor 1",
+                "Nvme: Composite (pci-0200)",
+                "CPU: Tctl",
+            ]
+        );
+        assert_eq!(device_bus_id("nvme-pci-0100"), "pci-0100");
+        assert_eq!(device_bus_id("acpitz-acpi-0"), "acpi-0");
+    }
+
+    #[test]
+    fn test_beep_enabled() {
+        let data = indoc! {"
+            it8792-isa-0a60
+            Adapter: ISA adapter
+            beep_enable:
+              beep_enable: 1.000
+            temp1:
+              temp1_beep: 1.000
+              temp1_input: 40.000
+              temp1_max: 127.000
+            temp2:
+              temp2_input: 45.000
+              temp2_beep: 0.000
+            temp3:
+              temp3_input: 50.000
+            fan1:
+              fan1_input: 1200.000
+              fan1_beep: 1.000
+        "};
+        let snapshot = LmSensorsSnapshot::from_sensors_output(data);
+
+        let temperatures = snapshot.temperatures(&TemperatureType::Celsius, &None);
+        let beeps = temperatures
+            .iter()
+            .map(|temp| (temp.temperature, temp.beep_enabled, temp.alarm))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            beeps,
+            [
+                (Some(40.0), Some(true), false),
+                (Some(45.0), Some(false), false),
+                (Some(50.0), None, false),
+            ]
+        );
+
+        let fans = snapshot.fans(&None);
+        assert_eq!(fans.len(), 1);
+        assert_eq!(fans[0].beep_enabled, Some(true));
+    }
+
+    #[test]
+    fn test_throttling() {
+        let data = indoc! {"
+            coretemp-isa-0000
+            Adapter: ISA adapter
+            Package id 0:
+              temp1_input: 95.000
+              temp1_crit: 100.000
+              temp1_crit_alarm: 1.000
+            Core 0:
+              temp2_input: 100.000
+              temp2_crit: 100.000
+              temp2_crit_alarm: 0.000
+            Core 1:
+              temp3_input: 101.000
+              temp3_crit: 100.000
+            Core 2:
+              temp4_input: 60.000
+              temp4_crit: 100.000
+              temp4_crit_alarm: 0.000
+              temp4_max_alarm: 1.000
+            Core 3:
+              temp5_input: 120.000
+        "};
+
+        let throttling = LmSensorsSnapshot::from_sensors_output(data)
+            .temperatures(&TemperatureType::Fahrenheit, &None)
+            .into_iter()
+            .map(|temp| temp.throttling)
+            .collect::<Vec<_>>();
+
+        assert_eq!(throttling, [true, true, true, false, false]);
+    }
+
+    #[test]
+    fn test_aliased_sensors() {
+        let data = indoc! {"
+            k10temp-pci-00c3
+            Adapter: PCI adapter
+            temp1:
+              temp1_input: 45.000
+            Tctl:
+              temp1_input: 45.000
+            Tccd1:
+              temp3_input: 45.000
+            Tccd2:
+              temp4_input: 41.000
+            Tccd2:
+              temp4_input: 41.000
+
+            nct6798-isa-0290
+            Adapter: ISA adapter
+            SYSTIN:
+              temp1_input: 30.000
+            temp1:
+              temp1_input: 30.000
+            CPUTIN:
+              temp2_input: 35.000
+            CPUTIN:
+              temp2_input: 36.000
+        "};
+
+        let temperatures = LmSensorsSnapshot::from_sensors_output(data)
+            .temperatures(&TemperatureType::Celsius, &None)
+            .into_iter()
+            .map(|temp| (temp.sensor_id, temp.temperature))
+            .collect::<Vec<_>>();
+
+        // Distinct readings are kept even with the same name.
+        assert_eq!(
+            temperatures,
+            [
+                ("k10temp-pci-00c3/Tctl".to_string(), Some(45.0)),
+                ("k10temp-pci-00c3/Tccd1".to_string(), Some(45.0)),
+                ("k10temp-pci-00c3/Tccd2".to_string(), Some(41.0)),
+                ("nct6798-isa-0290/SYSTIN".to_string(), Some(30.0)),
+                ("nct6798-isa-0290/CPUTIN".to_string(), Some(35.0)),
+                ("nct6798-isa-0290/CPUTIN".to_string(), Some(36.0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sensor_names() {
+        let data = indoc! {"
+            k10temp-pci-00c3
+            Adapter: PCI adapter
+            Tctl:
+              temp1_input: 45.000
+
+            nct6798-isa-0290
+            Adapter: ISA adapter
+            fan1:
+              fan1_input: 1200.000
+            in0:
+              in0_input: 0.984
+        "};
+        let snapshot = LmSensorsSnapshot::from_sensors_output(data);
+
+        let name = |sensor_type, device: &str, raw_name: &str, friendly_name: &str| SensorName {
+            sensor_type,
+            device: device.to_string(),
+            raw_name: raw_name.to_string(),
+            friendly_name: friendly_name.to_string(),
+        };
+
+        assert_eq!(
+            snapshot.sensor_names(&None),
+            [
+                name("temperature", "k10temp-pci-00c3", "Tctl", "CPU: Tctl"),
+                name("fan", "nct6798-isa-0290", "fan1", "nct6798: fan1"),
+                name("voltage", "nct6798-isa-0290", "in0", "nct6798: in0"),
+            ]
+        );
+
+        let filter = Some(Filter::new(true, vec![Regex::new("nct6798").unwrap()]));
+        assert_eq!(snapshot.sensor_names(&filter).len(), 1);
+    }
+
+    #[test]
+    fn test_for_each_temperature() {
+        let data = indoc! {"
+            k10temp-pci-00c3
+            Adapter: PCI adapter
+            Tctl:
+              temp1_input: 45.000
+            Tccd1:
+              temp3_input: 40.000
+
+            nct6798-isa-0290
+            Adapter: ISA adapter
+            fan1:
+              fan1_input: 1200.000
+            SYSTIN:
+              temp1_input: 30.000
+        "};
+        let snapshot = LmSensorsSnapshot::from_sensors_output(data);
+        let filter = Some(Filter::new(true, vec![Regex::new("Tccd1").unwrap()]));
+
+        let mut seen = Vec::new();
+        snapshot.for_each_temperature(&TemperatureType::Celsius, &filter, |temp| {
+            seen.push((temp.name, temp.temperature))
+        });
+
+        assert_eq!(
+            seen,
+            [
+                ("CPU: Tctl".to_string(), Some(45.0)),
+                ("nct6798: SYSTIN".to_string(), Some(30.0)),
+            ]
+        );
+        assert_eq!(
+            snapshot
+                .temperatures(&TemperatureType::Celsius, &filter)
+                .len(),
+            seen.len()
+        );
+    }
+
+    #[test]
+    fn test_temperature_tree() {
+        let data = indoc! {"
+            k10temp-pci-00c3
+            Adapter: PCI adapter
+            Tctl:
+              temp1_input: 45.000
+            Tccd1:
+              temp3_input: 40.000
+
+            nct6798-isa-0290
+            fan1:
+              fan1_input: 1200.000
+
+            acpitz-acpi-0
+            Adapter: ACPI interface
+            temp1:
+              temp1_input: 27.800
+        "};
+        let snapshot = LmSensorsSnapshot::from_sensors_output(data);
+
+        let tree = snapshot.temperature_tree(&TemperatureType::Celsius, &None);
+        let devices = tree
+            .iter()
+            .map(|device| (device.device.as_str(), device.sensors.len()))
+            .collect::<Vec<_>>();
+        assert_eq!(devices, [("k10temp-pci-00c3", 2), ("acpitz-acpi-0", 1)]);
+        assert_eq!(tree[0].adapter.as_deref(), Some("PCI adapter"));
+        assert_eq!(tree[0].sensors[1].name, "CPU: Tccd1");
+
+        let filter = Some(Filter::new(true, vec![Regex::new("k10temp").unwrap()]));
+        let tree = snapshot.temperature_tree(&TemperatureType::Celsius, &filter);
+        assert_eq!(tree.len(), 1);
+        assert_eq!(tree[0].device, "acpitz-acpi-0");
+    }
+
+    #[test]
+    fn test_sensor_order() {
+        let data = indoc! {"
+            nvme-pci-0100
+            Adapter: PCI adapter
+            Composite:
+              temp1_input: 30.850
+
+            k10temp-pci-00c3
+            Adapter: PCI adapter
+            Tctl:
+              temp1_input: 45.000
+            Tccd1:
+              temp3_input: 40.000
+
+            acpitz-acpi-0
+            Adapter: ACPI interface
+            temp1:
+              temp1_input: 27.800
+        "};
+
+        let mut temperatures = LmSensorsSnapshot::from_sensors_output(data)
+            .temperatures(&TemperatureType::Celsius, &None);
+        let names = |temperatures: &[TempHarvest]| {
+            temperatures
+                .iter()
+                .map(|temp| temp.name.clone())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            names(&temperatures),
+            [
+                "Nvme: Composite",
+                "CPU: Tctl",
+                "CPU: Tccd1",
+                "acpitz: temp1"
+            ]
+        );
+
+        sort_temperatures_by_name(&mut temperatures);
+        assert_eq!(
+            names(&temperatures),
+            [
+                "CPU: Tccd1",
+                "CPU: Tctl",
+                "Nvme: Composite",
+                "acpitz: temp1"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sensor_ids() {
+        let data = indoc! {"
+            nvme-pci-0100
+            Adapter: PCI adapter
+            Composite:
+              temp1_input: 30.850
+
+            nvme-pci-0200
+            Adapter: PCI adapter
+            Composite:
+              temp1_input: 35.850
+        "};
+
+        let snapshot = LmSensorsSnapshot::from_sensors_output(data)
+            .with_name_rules(vec![NameRule::substring("nvme", "Disk")]);
+        let ids = snapshot
+            .temperatures(&TemperatureType::Celsius, &None)
+            .into_iter()
+            .map(|temp| temp.sensor_id)
+            .collect::<Vec<_>>();
+
+        assert_eq!(ids, ["nvme-pci-0100/Composite", "nvme-pci-0200/Composite"]);
+    }
+
+    #[test]
+    fn test_truncated_output() {
+        let data = indoc! {"
+            k10temp-pci-00c3
+            Adapter: PCI adapter
+            Tctl:
+              temp1_input: 45.125
+
+            nvme-pci-0100
+            Adapter: PCI adapter
+            Composite:
+              temp1_input: 38.850
+              temp1_max: 81.850
+        "};
+
+        // Cut the output off at every possible point; none should panic, and a
+        // trailing device is only kept once it has a sensor.
+        for end in (0..=data.len()).filter(|&end| data.is_char_boundary(end)) {
+            let devices = parse_lm_sensors_data(&data[..end]);
+            assert!(devices.len() <= 2);
+            assert!(devices.iter().all(|device| !device.sensors.is_empty()));
+        }
+
+        let devices = parse_lm_sensors_data("k10temp-pci-00c3\nAdapter: PCI adapter\nTctl:\n");
+        assert!(devices.is_empty());
+
+        let devices = parse_lm_sensors_data(&data[..data.find("Composite").unwrap()]);
+        assert_eq!(devices.len(), 1);
+        assert_eq!(devices[0].name, "k10temp-pci-00c3");
+
+        assert_eq!(
+            format_friendly_names("-isa-0000", "temp1", &[]),
+            "-isa-0000: temp1"
+        );
+    }
+
+    #[test]
+    fn test_crlf_and_bom() {
+        let data = "\u{feff}k10temp-pci-00c3\r\nAdapter: PCI adapter\r\nTctl:\r\n  temp1_input: 40.000\r\n\r\nnvme-pci-0100\r\nAdapter: PCI adapter\r\nComposite:\r\n  temp1_input: 35.000\r\n\r\n\r\n";
+        let devices = parse_lm_sensors_data(data);
+
+        assert_eq!(devices.len(), 2);
+        assert_eq!(devices[0].name, "k10temp-pci-00c3");
+        assert_eq!(devices[0].adapter, "PCI adapter");
+        assert_eq!(devices[0].sensors[0].name, "Tctl");
+        assert_eq!(devices[0].sensors[0].value, Some(40.0));
+        assert_eq!(devices[1].name, "nvme-pci-0100");
+        assert_eq!(devices[1].sensors[0].value, Some(35.0));
+    }
+
+    #[test]
+    fn test_tab_indentation() {
+        let data = "k10temp-pci-00c3\nAdapter: PCI adapter\nTctl:\n\ttemp1_input: 45.000\n\ttemp1_max: 70.000\nTccd1:\n\t  temp3_input: 40.000\n\nnvme-pci-0100\nAdapter: PCI adapter\nComposite:\n\t\ttemp1_input: 30.850\n";
+        let devices = parse_lm_sensors_data(data);
+
+        assert_eq!(devices.len(), 2);
+        assert_eq!(devices[0].adapter, "PCI adapter");
+        assert_eq!(devices[0].sensors.len(), 2);
+        assert_eq!(devices[0].sensors[0].name, "Tctl");
+        assert_eq!(devices[0].sensors[0].value, Some(45.0));
+        assert_eq!(devices[0].sensors[0].high, Some(70.0));
+        assert_eq!(devices[0].sensors[1].name, "Tccd1");
+        assert_eq!(devices[0].sensors[1].value, Some(40.0));
+        assert_eq!(devices[1].name, "nvme-pci-0100");
+        assert_eq!(devices[1].sensors[0].value, Some(30.85));
+    }
+
+    #[test]
+    fn test_extra_blank_lines() {
+        let data = indoc! {"
+
+            k10temp-pci-00c3
+            Adapter: PCI adapter
+
+            Tctl:
+              temp1_input: 40.000
+
+
+            nvme-pci-0100
+            Adapter: PCI adapter
+            Composite:
+              temp1_input: 35.000
+
+
+
+            acpitz-acpi-0
+            Adapter: ACPI interface
+            temp1:
+              temp1_input: 27.800
+
+
+        "};
+
+        let devices = parse_lm_sensors_data(data);
+        assert_eq!(devices.len(), 3);
+        assert!(devices.iter().all(|device| device.sensors.len() == 1));
+
+        assert_eq!(devices[0].name, "k10temp-pci-00c3");
+        assert_eq!(devices[0].sensors[0].name, "Tctl");
+        assert_eq!(devices[0].sensors[0].value, Some(40.0));
+        assert_eq!(devices[1].name, "nvme-pci-0100");
+        assert_eq!(devices[1].sensors[0].value, Some(35.0));
+        assert_eq!(devices[2].name, "acpitz-acpi-0");
+        assert_eq!(devices[2].sensors[0].value, Some(27.8));
+    }
+
+    #[test]
+    fn test_duplicate_devices() {
+        let data = indoc! {"
+            k10temp-pci-00c3
+            Adapter: PCI adapter
+            Tctl:
+              temp1_input: 40.000
+            Tccd1:
+              temp3_input: 38.000
+
+            nvme-pci-0100
+            Adapter: PCI adapter
+            Composite:
+              temp1_input: 35.000
+
+            k10temp-pci-00c3
+            Adapter: PCI adapter
+            Tctl:
+              temp1_input: 42.000
+            Tccd2:
+              temp4_input: 39.000
+        "};
+
+        let devices = parse_lm_sensors_data(data);
+        assert_eq!(devices.len(), 2);
+        assert_eq!(devices[0].name, "k10temp-pci-00c3");
+        assert_eq!(devices[1].name, "nvme-pci-0100");
+
+        let sensors = devices[0]
+            .sensors
+            .iter()
+            .map(|sensor| (sensor.name.as_str(), sensor.value))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            sensors,
+            [
+                ("Tctl", Some(42.0)),
+                ("Tccd1", Some(38.0)),
+                ("Tccd2", Some(39.0))
+            ]
+        );
+    }
+
+    /// Returns the harvested `(name, temperature)` pairs from raw `sensors -u`
+    /// output.
+    fn harvested_temperatures(data: &str) -> Vec<(String, Option<f32>)> {
+        parse_temperature_data(data, &TemperatureType::Celsius, &None)
+            .into_iter()
+            .map(|temp| (temp.name, temp.temperature))
+            .collect()
+    }
+
+    #[test]
+    fn test_nvme_capture() {
+        let data = indoc! {"
+            nvme-pci-0100
+            Adapter: PCI adapter
+            Composite:
+              temp1_input: 38.850
+              temp1_max: 81.850
+              temp1_min: -273.150
+              temp1_crit: 84.850
+              temp1_alarm: 0.000
+            Sensor 1:
+              temp2_input: 38.850
+              temp2_max: 65261.850
+              temp2_min: -273.150
+            Sensor 2:
+              temp3_input: 43.850
+              temp3_max: 65261.850
+              temp3_min: -273.150
+
+        "};
+
+        assert_eq!(
+            harvested_temperatures(data),
+            [
+                ("Nvme: Composite".to_string(), Some(38.85)),
+                ("Nvme: Sensor 1".to_string(), Some(38.85)),
+                ("Nvme: Sensor 2".to_string(), Some(43.85)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_k10temp_capture() {
+        let data = indoc! {"
+            k10temp-pci-00c3
+            Adapter: PCI adapter
+            Tctl:
+              temp1_input: 48.625
+            Tccd1:
+              temp3_input: 42.750
+            Tccd2:
+              temp4_input: 41.500
+
+            amdgpu-pci-0300
+            Adapter: PCI adapter
+            vddgfx:
+              in0_input: 0.731
+            edge:
+              temp1_input: 45.000
+              temp1_crit: 100.000
+              temp1_crit_hyst: -273.150
+            PPT:
+              power1_average: 9.000
+              power1_input: 9.000
+              power1_cap: 186.000
+
+        "};
+
+        assert_eq!(
+            harvested_temperatures(data),
+            [
+                ("CPU: Tctl".to_string(), Some(48.625)),
+                ("CPU: Tccd1".to_string(), Some(42.75)),
+                ("CPU: Tccd2".to_string(), Some(41.5)),
+                ("Gpu: Edge".to_string(), Some(45.0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_coretemp_capture() {
+        let data = indoc! {"
+            coretemp-isa-0000
+            Adapter: ISA adapter
+            Package id 0:
+              temp1_input: 52.000
+              temp1_max: 100.000
+              temp1_crit: 100.000
+              temp1_crit_alarm: 0.000
+            Core 0:
+              temp2_input: 49.000
+              temp2_max: 100.000
+              temp2_crit: 100.000
+              temp2_crit_alarm: 0.000
+            Core 4:
+              temp6_input: 51.000
+              temp6_max: 100.000
+              temp6_crit: 100.000
+              temp6_crit_alarm: 0.000
+
+            coretemp-isa-0001
+            Adapter: ISA adapter
+            Package id 1:
+              temp1_input: 47.000
+              temp1_max: 100.000
+              temp1_crit: 100.000
+              temp1_crit_alarm: 0.000
+
+            pch_cannonlake-virtual-0
+            Adapter: Virtual device
+            temp1:
+              temp1_input: 58.000
+
+        "};
+
+        assert_eq!(
+            harvested_temperatures(data),
+            [
+                ("CPU: Package".to_string(), Some(52.0)),
+                ("CPU: Core 0".to_string(), Some(49.0)),
+                ("CPU: Core 4".to_string(), Some(51.0)),
+                ("CPU: Package 1".to_string(), Some(47.0)),
+                ("pch_cannonlake: temp1".to_string(), Some(58.0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_gpu_capture() {
+        let data = indoc! {"
+            amdgpu-pci-0c00
+            Adapter: PCI adapter
+            vddgfx:
+              in0_input: 0.806
+            fan1:
+              fan1_input: 0.000
+              fan1_min: 0.000
+              fan1_max: 3300.000
+            edge:
+              temp1_input: 41.000
+              temp1_crit: 100.000
+              temp1_emergency: 105.000
+            junction:
+              temp2_input: 43.000
+              temp2_crit: 110.000
+              temp2_emergency: 115.000
+            mem:
+              temp3_input: 48.000
+              temp3_crit: 100.000
+              temp3_emergency: 105.000
+
+            nouveau-pci-0100
+            Adapter: PCI adapter
+            GPU core:
+              in0_input: 0.900
+            temp1:
+              temp1_input: 38.000
+              temp1_max: 95.000
+              temp1_crit: 105.000
+
+        "};
+
+        assert_eq!(
+            harvested_temperatures(data),
+            [
+                ("Gpu: Edge".to_string(), Some(41.0)),
+                ("Gpu: Junction".to_string(), Some(43.0)),
+                ("Gpu: Memory".to_string(), Some(48.0)),
+                ("Gpu: temp1".to_string(), Some(38.0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_nct6xxx_capture() {
+        let data = indoc! {"
+            nct6798-isa-0290
+            Adapter: ISA adapter
+            in0:
+              in0_input: 0.296
+              in0_min: 0.000
+              in0_max: 1.744
+              in0_alarm: 0.000
+              in0_beep: 0.000
+            fan2:
+              fan2_input: 1034.000
+              fan2_min: 0.000
+              fan2_alarm: 0.000
+              fan2_beep: 0.000
+              fan2_pulses: 2.000
+            SYSTIN:
+              temp1_input: 31.000
+              temp1_max: 80.000
+              temp1_max_hyst: 75.000
+              temp1_alarm: 0.000
+              temp1_type: 4.000
+              temp1_offset: 0.000
+              temp1_beep: 0.000
+            CPUTIN:
+              temp2_input: 36.500
+              temp2_max: 80.000
+              temp2_max_hyst: 75.000
+              temp2_alarm: 0.000
+              temp2_type: 4.000
+              temp2_offset: 0.000
+              temp2_beep: 0.000
+            AUXTIN0:
+              temp3_input: -62.000
+              temp3_type: 4.000
+              temp3_offset: 0.000
+            PCH_CHIP_CPU_MAX_TEMP:
+              temp7_input: 0.000
+            intrusion0:
+              intrusion0_alarm: 1.000
+              intrusion0_beep: 0.000
+
+        "};
+
+        assert_eq!(
+            harvested_temperatures(data),
+            [
+                ("nct6798: SYSTIN".to_string(), Some(31.0)),
+                ("nct6798: CPUTIN".to_string(), Some(36.5)),
+                // Unconnected inputs read very low, but without a valid range
+                // they're kept like any other reading.
+                ("nct6798: AUXTIN0".to_string(), Some(-62.0)),
+                ("nct6798: PCH_CHIP_CPU_MAX_TEMP".to_string(), Some(0.0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_json() {
+        let data = indoc! {r#"
+            {
+               "nvme-pci-0100":{
+                  "Adapter": "PCI adapter",
+                  "Composite":{
+                     "temp1_input": 30.850,
+                     "temp1_max": 81.850,
+                     "temp1_crit": 84.850,
+                     "temp1_alarm": 0.000
+                  }
+               },
+               "amdgpu-pci-0c00":{
+                  "Adapter": "PCI adapter",
+                  "vddgfx":{
+                     "in0_input": 0.725
+                  },
+                  "edge":{
+                     "temp1_input": 41.000
+                  }
+               }
+            }
+        "#};
+
+        assert!(is_json_output(data));
+        assert!(!is_json_output("k10temp-pci-00c3\nAdapter: PCI adapter\n"));
+
+        let devices = parse_lm_sensors_json(data);
+        assert_eq!(devices.len(), 2);
+
+        assert_eq!(devices[0].name, "nvme-pci-0100");
+        assert_eq!(devices[0].adapter, "PCI adapter");
+        assert_eq!(devices[0].sensors.len(), 1);
+        assert_eq!(devices[0].sensors[0].name, "Composite");
+        assert_eq!(devices[0].sensors[0].value, Some(30.85));
+        assert_eq!(devices[0].sensors[0].crit, Some(84.85));
+
+        assert_eq!(devices[1].name, "amdgpu-pci-0c00");
+        assert_eq!(devices[1].sensors.len(), 2);
+        assert_eq!(devices[1].sensors[0].name, "vddgfx");
+        assert!(matches!(
+            devices[1].sensors[0].sensor_type,
+            LmSensorsSensorType::Voltage
+        ));
+        assert_eq!(devices[1].sensors[1].name, "edge");
+        assert!(matches!(
+            devices[1].sensors[1].sensor_type,
+            LmSensorsSensorType::Temp
+        ));
+    }
 }
@@ -0,0 +1,155 @@
+//! Caching of sensor readings, to avoid running `sensors` more than needed.
+
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+
+use super::{get_all_sensor_data_with_name_rules, SensorBundle};
+use crate::{app::filter::Filter, data_collection::temperature::NameRule};
+
+/// The least time to leave between runs of `sensors` during data collection,
+/// whatever the refresh rate is.
+pub const MIN_SENSORS_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Caches the last [`SensorBundle`] for a time-to-live, so that multiple
+/// requests within the same refresh window don't each run `sensors`.
+///
+/// This also works as a hard floor on how often `sensors` is run, as reading some
+/// i2c buses too often can disturb them. Failed reads count towards the TTL too,
+/// so a failing `sensors` isn't retried on every call.
+///
+/// The clock is injectable for testing, and defaults to [`Instant::now`].
+#[derive(Debug)]
+pub struct SensorCache<C = fn() -> Instant> {
+    ttl: Duration,
+    filter: Option<Filter>,
+    name_rules: Vec<NameRule>,
+    clock: C,
+    last_refresh: Option<Instant>,
+    cached: Option<SensorBundle>,
+}
+
+impl SensorCache {
+    /// Creates a cache for sensors that pass `filter`, which reuses readings that
+    /// are younger than `ttl`.
+    pub fn new(ttl: Duration, filter: Option<Filter>) -> Self {
+        Self::with_clock(ttl, filter, Instant::now)
+    }
+}
+
+impl<C: Fn() -> Instant> SensorCache<C> {
+    /// Creates a cache that uses `clock` to tell the time.
+    pub fn with_clock(ttl: Duration, filter: Option<Filter>, clock: C) -> Self {
+        Self {
+            ttl,
+            filter,
+            name_rules: Vec::new(),
+            clock,
+            last_refresh: None,
+            cached: None,
+        }
+    }
+
+    /// Sets the rules used to name sensors, before the built-in ones. This
+    /// throws away the cached readings, as they were named with the old rules.
+    pub fn set_name_rules(&mut self, name_rules: Vec<NameRule>) {
+        self.name_rules = name_rules;
+        self.invalidate();
+    }
+
+    /// Returns the cached readings if they're younger than the TTL, and reads
+    /// the sensors again otherwise. This fails if called within the TTL of a
+    /// read that failed, as there are no readings to return.
+    pub fn get(&mut self) -> Result<&SensorBundle> {
+        let filter = self.filter.clone();
+        let name_rules = self.name_rules.clone();
+        self.get_or_refresh(|| get_all_sensor_data_with_name_rules(&filter, &name_rules))
+    }
+
+    /// Throws away the cached readings, so the next [`SensorCache::get`] reads
+    /// the sensors again.
+    pub fn invalidate(&mut self) {
+        self.last_refresh = None;
+        self.cached = None;
+    }
+
+    fn get_or_refresh(
+        &mut self, refresh: impl FnOnce() -> Result<SensorBundle>,
+    ) -> Result<&SensorBundle> {
+        let now = (self.clock)();
+
+        let is_fresh = self
+            .last_refresh
+            .is_some_and(|last_refresh| now.saturating_duration_since(last_refresh) < self.ttl);
+
+        if !is_fresh {
+            self.last_refresh = Some(now);
+            // Drop the old readings first, so a failed read doesn't leave them
+            // looking current.
+            self.cached = None;
+            self.cached = Some(refresh()?);
+        }
+
+        self.cached
+            .as_ref()
+            .ok_or_else(|| anyhow!("sensors was read too recently, and the last read failed"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+    use crate::data_collection::temperature::lm_sensors::FanHarvest;
+
+    #[test]
+    fn test_sensor_cache() {
+        let start = Instant::now();
+        let elapsed = Cell::new(Duration::ZERO);
+        let refreshes = Cell::new(0.0);
+
+        let mut cache =
+            SensorCache::with_clock(Duration::from_secs(1), None, || start + elapsed.get());
+        let get = |cache: &mut SensorCache<_>| {
+            cache
+                .get_or_refresh(|| {
+                    refreshes.set(refreshes.get() + 1.0);
+                    Ok(SensorBundle {
+                        fans: vec![FanHarvest {
+                            rpm: refreshes.get(),
+                            ..Default::default()
+                        }],
+                        ..Default::default()
+                    })
+                })
+                .unwrap()
+                .fans[0]
+                .rpm
+        };
+
+        assert_eq!(get(&mut cache), 1.0);
+
+        elapsed.set(Duration::from_millis(999));
+        assert_eq!(get(&mut cache), 1.0);
+
+        elapsed.set(Duration::from_millis(1000));
+        assert_eq!(get(&mut cache), 2.0);
+
+        cache.invalidate();
+        assert_eq!(get(&mut cache), 3.0);
+
+        // Failed reads count towards the TTL, so the next call doesn't retry.
+        elapsed.set(Duration::from_millis(2500));
+        assert!(cache
+            .get_or_refresh(|| anyhow::bail!("sensors failed"))
+            .is_err());
+
+        elapsed.set(Duration::from_millis(3000));
+        assert!(cache.get_or_refresh(|| unreachable!()).is_err());
+        assert_eq!(refreshes.get(), 3.0);
+
+        elapsed.set(Duration::from_millis(3500));
+        assert_eq!(get(&mut cache), 4.0);
+    }
+}
@@ -0,0 +1,557 @@
+//! Exporting sensor data into other formats for scripting and monitoring.
+
+use std::{
+    fmt::Write,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Result;
+use serde::Serialize;
+
+use super::{get_all_sensor_data, SensorBundle, TemperatureType};
+use crate::app::filter::Filter;
+
+/// A single sensor reading, in a uniform shape regardless of the type of sensor.
+#[derive(Debug, Serialize)]
+struct Reading<'a> {
+    /// A stable identifier for the sensor, if the sensor type has one. This
+    /// should be used to key sensors rather than `name`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<&'a str>,
+    name: &'a str,
+    value: Option<f32>,
+    unit: &'static str,
+    #[serde(rename = "type")]
+    sensor_type: &'static str,
+}
+
+impl<'a> Reading<'a> {
+    fn new(
+        sensor_type: &'static str, unit: &'static str, name: &'a str, value: Option<f32>,
+    ) -> Self {
+        Self {
+            id: None,
+            name,
+            value,
+            unit,
+            sensor_type,
+        }
+    }
+
+    fn with_id(mut self, id: &'a str) -> Self {
+        self.id = (!id.is_empty()).then_some(id);
+        self
+    }
+}
+
+/// All readings of a [`SensorBundle`], grouped by type.
+#[derive(Debug, Serialize)]
+struct Readings<'a> {
+    temperatures: Vec<Reading<'a>>,
+    fans: Vec<Reading<'a>>,
+    voltages: Vec<Reading<'a>>,
+    powers: Vec<Reading<'a>>,
+    currents: Vec<Reading<'a>>,
+    humidities: Vec<Reading<'a>>,
+    pwms: Vec<Reading<'a>>,
+    intrusions: Vec<Reading<'a>>,
+}
+
+impl<'a> Readings<'a> {
+    fn new(bundle: &'a SensorBundle) -> Self {
+        Self {
+            temperatures: bundle
+                .temperatures
+                .iter()
+                .map(|temp| {
+                    Reading::new(
+                        "temperature",
+                        bundle.unit.unit_symbol(),
+                        &temp.name,
+                        temp.temperature,
+                    )
+                    .with_id(&temp.sensor_id)
+                })
+                .collect(),
+            fans: bundle
+                .fans
+                .iter()
+                .map(|fan| {
+                    Reading::new("fan", "RPM", &fan.name, fan.present.then_some(fan.rpm))
+                        .with_id(&fan.sensor_id)
+                })
+                .collect(),
+            voltages: bundle
+                .voltages
+                .iter()
+                .map(|voltage| {
+                    Reading::new("voltage", "V", &voltage.name, voltage.volts)
+                        .with_id(&voltage.sensor_id)
+                })
+                .collect(),
+            powers: bundle
+                .powers
+                .iter()
+                .map(|power| {
+                    Reading::new("power", "W", &power.name, power.watts).with_id(&power.sensor_id)
+                })
+                .collect(),
+            currents: bundle
+                .currents
+                .iter()
+                .map(|current| {
+                    Reading::new("current", "A", &current.name, Some(current.amps))
+                        .with_id(&current.sensor_id)
+                })
+                .collect(),
+            humidities: bundle
+                .humidities
+                .iter()
+                .map(|humidity| {
+                    Reading::new("humidity", "%", &humidity.name, Some(humidity.percent))
+                        .with_id(&humidity.sensor_id)
+                })
+                .collect(),
+            pwms: bundle
+                .pwms
+                .iter()
+                .map(|pwm| {
+                    Reading::new("pwm", "%", &pwm.name, Some(pwm.percent)).with_id(&pwm.sensor_id)
+                })
+                .collect(),
+            intrusions: bundle
+                .intrusions
+                .iter()
+                .map(|intrusion| {
+                    let value = if intrusion.triggered { 1.0 } else { 0.0 };
+                    Reading::new("intrusion", "", &intrusion.name, Some(value))
+                        .with_id(&intrusion.sensor_id)
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Readings<'_> {
+    /// Pairs each group of readings with its Prometheus metric name.
+    fn metrics(&self) -> [(&'static str, &[Reading<'_>]); 8] {
+        [
+            ("bottom_temperature_celsius", &self.temperatures),
+            ("bottom_fan_rpm", &self.fans),
+            ("bottom_voltage_volts", &self.voltages),
+            ("bottom_power_watts", &self.powers),
+            ("bottom_current_amperes", &self.currents),
+            ("bottom_humidity_percent", &self.humidities),
+            ("bottom_pwm_percent", &self.pwms),
+            ("bottom_intrusion_triggered", &self.intrusions),
+        ]
+    }
+}
+
+/// Escapes a label value as per the Prometheus text format.
+fn escape_label_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Serializes a [`SensorBundle`] in the Prometheus text exposition format, one
+/// gauge per type of sensor. Readings without a value are left out, and
+/// temperatures are always in Celsius.
+fn bundle_to_prometheus(bundle: &SensorBundle) -> String {
+    let mut bundle = bundle.clone();
+    bundle.convert_temperatures(TemperatureType::Celsius);
+
+    let readings = Readings::new(&bundle);
+    let mut out = String::new();
+
+    for (metric, readings) in readings.metrics() {
+        let mut readings = readings
+            .iter()
+            .filter_map(|reading| Some((reading.id, reading.name, reading.value?)))
+            .peekable();
+
+        if readings.peek().is_none() {
+            continue;
+        }
+
+        let _ = writeln!(out, "# TYPE {metric} gauge");
+        for (id, name, value) in readings {
+            let _ = write!(out, "{metric}{{");
+            if let Some(id) = id {
+                let _ = write!(out, "id=\"{}\",", escape_label_value(id));
+            }
+            let _ = writeln!(out, "sensor=\"{}\"}} {value}", escape_label_value(name));
+        }
+    }
+
+    out
+}
+
+/// Quotes a CSV field if it contains a comma, quote, or newline.
+fn escape_csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Serializes a [`SensorBundle`] as CSV, with a `type,name,value,unit` header
+/// and one row per sensor. Readings without a value have an empty value field,
+/// and temperatures are always in Celsius.
+fn bundle_to_csv(bundle: &SensorBundle) -> String {
+    let mut bundle = bundle.clone();
+    bundle.convert_temperatures(TemperatureType::Celsius);
+
+    let readings = Readings::new(&bundle);
+    let mut out = String::from("type,name,value,unit\n");
+
+    for reading in readings
+        .metrics()
+        .into_iter()
+        .flat_map(|(_, readings)| readings)
+    {
+        let _ = writeln!(
+            out,
+            "{},{},{},{}",
+            reading.sensor_type,
+            escape_csv_field(reading.name),
+            reading
+                .value
+                .map(|value| value.to_string())
+                .unwrap_or_default(),
+            reading.unit
+        );
+    }
+
+    out
+}
+
+/// Escapes a tag value as per the InfluxDB line protocol. Newlines aren't allowed
+/// at all, so they are escaped like in the other formats.
+fn escape_influx_tag(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            ',' | '=' | ' ' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push_str("\\n"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Serializes a [`SensorBundle`] in the InfluxDB line protocol, with one row per
+/// reading, measured by sensor type and tagged with the sensor name and `host`.
+/// Readings without a value are left out, and temperatures are always in
+/// Celsius. Every row shares `timestamp_ns`.
+fn bundle_to_influx_line(bundle: &SensorBundle, host: &str, timestamp_ns: u128) -> String {
+    let mut bundle = bundle.clone();
+    bundle.convert_temperatures(TemperatureType::Celsius);
+
+    let readings = Readings::new(&bundle);
+    let host = escape_influx_tag(host);
+    let mut out = String::new();
+
+    for reading in readings
+        .metrics()
+        .into_iter()
+        .flat_map(|(_, readings)| readings)
+    {
+        let Some(value) = reading.value else {
+            continue;
+        };
+
+        let _ = write!(
+            out,
+            "{},sensor={}",
+            reading.sensor_type,
+            escape_influx_tag(reading.name)
+        );
+        // Empty tag values aren't allowed, so leave the tag out instead.
+        if !host.is_empty() {
+            let _ = write!(out, ",host={host}");
+        }
+        let _ = writeln!(out, " value={value} {timestamp_ns}");
+    }
+
+    out
+}
+
+/// Serializes a [`SensorBundle`] as JSON. Each type of sensor is an array of
+/// `{"name", "value", "unit", "type"}` objects.
+fn bundle_to_json(bundle: &SensorBundle) -> Result<String> {
+    Ok(serde_json::to_string(&Readings::new(bundle))?)
+}
+
+/// Returns every sensor reading from lm_sensors as JSON, for scripting.
+pub fn sensors_to_json(filter: &Option<Filter>) -> Result<String> {
+    bundle_to_json(&get_all_sensor_data(filter)?)
+}
+
+/// Returns every sensor reading from lm_sensors in the Prometheus text format.
+/// Temperatures are always in Celsius, regardless of the display unit.
+pub fn sensors_to_prometheus(filter: &Option<Filter>) -> Result<String> {
+    Ok(bundle_to_prometheus(&get_all_sensor_data(filter)?))
+}
+
+/// Returns every sensor reading from lm_sensors as CSV. Temperatures are always
+/// in Celsius, regardless of the display unit.
+pub fn sensors_to_csv(filter: &Option<Filter>) -> Result<String> {
+    Ok(bundle_to_csv(&get_all_sensor_data(filter)?))
+}
+
+/// Returns every sensor reading from lm_sensors in the InfluxDB line protocol,
+/// tagged with `host_tag`. Temperatures are always in Celsius.
+pub fn sensors_to_influx_line(filter: &Option<Filter>, host_tag: &str) -> Result<String> {
+    let timestamp_ns = SystemTime::now().duration_since(UNIX_EPOCH)?.as_nanos();
+    Ok(bundle_to_influx_line(
+        &get_all_sensor_data(filter)?,
+        host_tag,
+        timestamp_ns,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+    use crate::data_collection::temperature::{
+        lm_sensors::{FanHarvest, PowerHarvest, VoltageHarvest},
+        TempHarvest,
+    };
+
+    #[test]
+    fn test_json() {
+        assert_eq!(
+            bundle_to_json(&SensorBundle::default()).unwrap(),
+            r#"{"temperatures":[],"fans":[],"voltages":[],"powers":[],"currents":[],"humidities":[],"pwms":[],"intrusions":[]}"#
+        );
+
+        let bundle = SensorBundle {
+            temperatures: vec![
+                TempHarvest {
+                    name: "CPU: Tctl".to_string(),
+                    sensor_id: "k10temp-pci-00c3/Tctl".to_string(),
+                    temperature: Some(54.5),
+                    ..Default::default()
+                },
+                TempHarvest {
+                    name: "acpitz: temp1".to_string(),
+                    temperature: None,
+                    ..Default::default()
+                },
+            ],
+            fans: vec![
+                FanHarvest {
+                    name: "AIO: fan1".to_string(),
+                    sensor_id: "nct6798-isa-0290/fan1".to_string(),
+                    rpm: 1200.0,
+                    present: true,
+                    ..Default::default()
+                },
+                FanHarvest {
+                    name: "MB: fan2".to_string(),
+                    present: false,
+                    ..Default::default()
+                },
+            ],
+            voltages: vec![VoltageHarvest {
+                name: "MB: in0".to_string(),
+                sensor_id: "nct6798-isa-0290/in0".to_string(),
+                volts: Some(1.5),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            bundle_to_json(&bundle).unwrap(),
+            concat!(
+                r#"{"temperatures":["#,
+                r#"{"id":"k10temp-pci-00c3/Tctl","name":"CPU: Tctl","value":54.5,"unit":"°C","type":"temperature"},"#,
+                r#"{"name":"acpitz: temp1","value":null,"unit":"°C","type":"temperature"}],"#,
+                r#""fans":[{"id":"nct6798-isa-0290/fan1","name":"AIO: fan1","value":1200.0,"unit":"RPM","type":"fan"},"#,
+                r#"{"name":"MB: fan2","value":null,"unit":"RPM","type":"fan"}],"#,
+                r#""voltages":[{"id":"nct6798-isa-0290/in0","name":"MB: in0","value":1.5,"unit":"V","type":"voltage"}],"#,
+                r#""powers":[],"currents":[],"humidities":[],"pwms":[],"intrusions":[]}"#
+            )
+        );
+    }
+
+    #[test]
+    fn test_prometheus() {
+        assert_eq!(bundle_to_prometheus(&SensorBundle::default()), "");
+
+        let bundle = SensorBundle {
+            temperatures: vec![
+                TempHarvest {
+                    name: "CPU: Tctl".to_string(),
+                    sensor_id: "k10temp-pci-00c3/Tctl".to_string(),
+                    temperature: Some(54.5),
+                    ..Default::default()
+                },
+                TempHarvest {
+                    name: "acpitz: temp1".to_string(),
+                    temperature: None,
+                    ..Default::default()
+                },
+                TempHarvest {
+                    name: "odd \"name\" \\ with\nnewline".to_string(),
+                    temperature: Some(30.0),
+                    ..Default::default()
+                },
+            ],
+            fans: vec![FanHarvest {
+                name: "AIO: fan1".to_string(),
+                sensor_id: "nct6798-isa-0290/fan1".to_string(),
+                rpm: 1200.0,
+                present: true,
+                ..Default::default()
+            }],
+            powers: vec![PowerHarvest {
+                name: "GPU: power1".to_string(),
+                sensor_id: "amdgpu-pci-0300/power1".to_string(),
+                watts: Some(45.0),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            bundle_to_prometheus(&bundle),
+            indoc! {r#"
+                # TYPE bottom_temperature_celsius gauge
+                bottom_temperature_celsius{id="k10temp-pci-00c3/Tctl",sensor="CPU: Tctl"} 54.5
+                bottom_temperature_celsius{sensor="odd \"name\" \\ with\nnewline"} 30
+                # TYPE bottom_fan_rpm gauge
+                bottom_fan_rpm{id="nct6798-isa-0290/fan1",sensor="AIO: fan1"} 1200
+                # TYPE bottom_power_watts gauge
+                bottom_power_watts{id="amdgpu-pci-0300/power1",sensor="GPU: power1"} 45
+            "#}
+        );
+    }
+
+    #[test]
+    fn test_influx_line() {
+        assert_eq!(
+            bundle_to_influx_line(&SensorBundle::default(), "box", 0),
+            ""
+        );
+
+        let bundle = SensorBundle {
+            temperatures: vec![
+                TempHarvest {
+                    name: "CPU: Tctl".to_string(),
+                    temperature: Some(54.0),
+                    ..Default::default()
+                },
+                TempHarvest {
+                    name: "MB: temp, package=1".to_string(),
+                    temperature: Some(98.6),
+                    ..Default::default()
+                },
+                TempHarvest {
+                    name: "acpitz: temp1".to_string(),
+                    temperature: None,
+                    ..Default::default()
+                },
+            ],
+            unit: TemperatureType::Fahrenheit,
+            fans: vec![FanHarvest {
+                name: "AIO: fan1".to_string(),
+                rpm: 1200.0,
+                present: true,
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            bundle_to_influx_line(&bundle, "my box", 1700000000000000000),
+            indoc! {r#"
+                temperature,sensor=CPU:\ Tctl,host=my\ box value=12.222222 1700000000000000000
+                temperature,sensor=MB:\ temp\,\ package\=1,host=my\ box value=37 1700000000000000000
+                fan,sensor=AIO:\ fan1,host=my\ box value=1200 1700000000000000000
+            "#}
+        );
+
+        assert_eq!(
+            bundle_to_influx_line(&bundle, "", 1),
+            indoc! {r#"
+                temperature,sensor=CPU:\ Tctl value=12.222222 1
+                temperature,sensor=MB:\ temp\,\ package\=1 value=37 1
+                fan,sensor=AIO:\ fan1 value=1200 1
+            "#}
+        );
+    }
+
+    #[test]
+    fn test_csv() {
+        assert_eq!(
+            bundle_to_csv(&SensorBundle::default()),
+            "type,name,value,unit\n"
+        );
+
+        let bundle = SensorBundle {
+            temperatures: vec![
+                TempHarvest {
+                    name: "CPU: temp, package".to_string(),
+                    temperature: Some(54.5),
+                    ..Default::default()
+                },
+                TempHarvest {
+                    name: "acpitz: temp1".to_string(),
+                    temperature: None,
+                    ..Default::default()
+                },
+            ],
+            voltages: vec![VoltageHarvest {
+                name: "MB: \"12V\"".to_string(),
+                volts: Some(12.1),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            bundle_to_csv(&bundle),
+            indoc! {r#"
+                type,name,value,unit
+                temperature,"CPU: temp, package",54.5,°C
+                temperature,acpitz: temp1,,°C
+                voltage,"MB: ""12V""",12.1,V
+            "#}
+        );
+
+        let bundle = SensorBundle {
+            temperatures: vec![TempHarvest {
+                name: "CPU: Tctl".to_string(),
+                temperature: Some(122.0),
+                ..Default::default()
+            }],
+            unit: TemperatureType::Fahrenheit,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            bundle_to_csv(&bundle),
+            indoc! {r#"
+                type,name,value,unit
+                temperature,CPU: Tctl,50,°C
+            "#}
+        );
+    }
+}
@@ -0,0 +1,422 @@
+//! Reads sensor data directly from `libsensors`, if the library can be loaded
+//! at runtime. This avoids both spawning `sensors` and parsing its
+//! human-readable output.
+//!
+//! libsensors isn't thread-safe, so every call into it goes through a single
+//! [`Mutex`].
+
+use std::{
+    ffi::{c_char, c_double, c_int, c_short, c_uint, c_void, CStr},
+    ptr,
+    sync::{Mutex, OnceLock},
+};
+
+use libloading::Library;
+
+use super::{parse_lm_sensors_sensor, LmSensorsDevice, LmSensorsSensor};
+
+/// The library names to try loading, in order.
+const LIBRARY_NAMES: [&str; 2] = ["libsensors.so.5", "libsensors.so"];
+
+/// Set in `sensors_subfeature.flags` if the subfeature is readable.
+const SENSORS_MODE_R: c_uint = 1;
+
+#[repr(C)]
+struct SensorsBusId {
+    bus_type: c_short,
+    nr: c_short,
+}
+
+#[repr(C)]
+struct SensorsChipName {
+    prefix: *mut c_char,
+    bus: SensorsBusId,
+    addr: c_int,
+    path: *mut c_char,
+}
+
+#[repr(C)]
+struct SensorsFeature {
+    name: *mut c_char,
+    number: c_int,
+    feature_type: c_int,
+    first_subfeature: c_int,
+    padding1: c_int,
+}
+
+#[repr(C)]
+struct SensorsSubfeature {
+    name: *mut c_char,
+    number: c_int,
+    subfeature_type: c_int,
+    mapping: c_int,
+    flags: c_uint,
+}
+
+type InitFn = unsafe extern "C" fn(*mut c_void) -> c_int;
+type GetDetectedChipsFn =
+    unsafe extern "C" fn(*const SensorsChipName, *mut c_int) -> *const SensorsChipName;
+type SnprintfChipNameFn = unsafe extern "C" fn(*mut c_char, usize, *const SensorsChipName) -> c_int;
+type GetAdapterNameFn = unsafe extern "C" fn(*const SensorsBusId) -> *const c_char;
+type GetFeaturesFn =
+    unsafe extern "C" fn(*const SensorsChipName, *mut c_int) -> *const SensorsFeature;
+type GetLabelFn =
+    unsafe extern "C" fn(*const SensorsChipName, *const SensorsFeature) -> *mut c_char;
+type GetAllSubfeaturesFn = unsafe extern "C" fn(
+    *const SensorsChipName,
+    *const SensorsFeature,
+    *mut c_int,
+) -> *const SensorsSubfeature;
+type GetValueFn = unsafe extern "C" fn(*const SensorsChipName, c_int, *mut c_double) -> c_int;
+
+/// The loaded and initialized `libsensors` functions we need.
+struct LibSensors {
+    // Never read, but must be kept alive for the function pointers to stay valid.
+    _library: Library,
+    get_detected_chips: GetDetectedChipsFn,
+    snprintf_chip_name: SnprintfChipNameFn,
+    get_adapter_name: GetAdapterNameFn,
+    get_features: GetFeaturesFn,
+    get_label: GetLabelFn,
+    get_all_subfeatures: GetAllSubfeaturesFn,
+    get_value: GetValueFn,
+}
+
+/// Copies a C string into an owned [`String`], returning [`None`] if it's null.
+///
+/// # Safety
+///
+/// `ptr` must either be null or point to a valid nul-terminated string.
+unsafe fn to_string(ptr: *const c_char) -> Option<String> {
+    (!ptr.is_null()).then(|| CStr::from_ptr(ptr).to_string_lossy().into_owned())
+}
+
+impl LibSensors {
+    /// Loads and initializes `libsensors`. Note that the library is only
+    /// initialized once, so chips that are added later on won't be picked up.
+    fn load() -> Option<Self> {
+        // SAFETY: Loading libsensors doesn't run any initialization routines that
+        // we need to worry about, and the symbol types match the libsensors 4+ API.
+        unsafe {
+            let library = LIBRARY_NAMES
+                .iter()
+                .find_map(|name| Library::new(name).ok())?;
+
+            let init = *library.get::<InitFn>(b"sensors_init\0").ok()?;
+            let get_detected_chips = *library
+                .get::<GetDetectedChipsFn>(b"sensors_get_detected_chips\0")
+                .ok()?;
+            let snprintf_chip_name = *library
+                .get::<SnprintfChipNameFn>(b"sensors_snprintf_chip_name\0")
+                .ok()?;
+            let get_adapter_name = *library
+                .get::<GetAdapterNameFn>(b"sensors_get_adapter_name\0")
+                .ok()?;
+            let get_features = *library
+                .get::<GetFeaturesFn>(b"sensors_get_features\0")
+                .ok()?;
+            let get_label = *library.get::<GetLabelFn>(b"sensors_get_label\0").ok()?;
+            let get_all_subfeatures = *library
+                .get::<GetAllSubfeaturesFn>(b"sensors_get_all_subfeatures\0")
+                .ok()?;
+            let get_value = *library.get::<GetValueFn>(b"sensors_get_value\0").ok()?;
+
+            // A null config file means libsensors will use its default configuration.
+            if init(ptr::null_mut()) != 0 {
+                return None;
+            }
+
+            Some(Self {
+                _library: library,
+                get_detected_chips,
+                snprintf_chip_name,
+                get_adapter_name,
+                get_features,
+                get_label,
+                get_all_subfeatures,
+                get_value,
+            })
+        }
+    }
+
+    /// Reads every detected chip into the same structures that parsing `sensors`
+    /// output would produce.
+    fn devices(&self) -> Vec<LmSensorsDevice> {
+        let mut devices = Vec::new();
+        let mut chip_nr: c_int = 0;
+
+        loop {
+            // SAFETY: A null match returns all chips, and `chip_nr` is the iteration state.
+            let chip = unsafe { (self.get_detected_chips)(ptr::null(), &mut chip_nr) };
+            if chip.is_null() {
+                break;
+            }
+
+            let mut name_buffer = [0 as c_char; 256];
+
+            // SAFETY: The buffer size passed matches the buffer, and `chip` is non-null.
+            let written = unsafe {
+                (self.snprintf_chip_name)(name_buffer.as_mut_ptr(), name_buffer.len(), chip)
+            };
+            if written < 0 {
+                continue;
+            }
+
+            // SAFETY: snprintf always nul-terminates on success.
+            let Some(name) = (unsafe { to_string(name_buffer.as_ptr()) }) else {
+                continue;
+            };
+
+            // SAFETY: `chip` is non-null and owned by libsensors.
+            let adapter =
+                unsafe { to_string((self.get_adapter_name)(&(*chip).bus)) }.unwrap_or_default();
+
+            devices.push(LmSensorsDevice {
+                name,
+                adapter,
+                sensors: self.sensors(chip),
+            });
+        }
+
+        devices
+    }
+
+    fn sensors(&self, chip: *const SensorsChipName) -> Vec<LmSensorsSensor> {
+        let mut sensors = Vec::new();
+        let mut feature_nr: c_int = 0;
+
+        loop {
+            // SAFETY: `chip` is a valid chip from libsensors, and `feature_nr` is the
+            // iteration state.
+            let feature = unsafe { (self.get_features)(chip, &mut feature_nr) };
+            if feature.is_null() {
+                break;
+            }
+
+            // SAFETY: The label is allocated by libsensors and must be freed by the
+            // caller.
+            let label = unsafe {
+                let label_ptr = (self.get_label)(chip, feature);
+                let label = to_string(label_ptr);
+                libc::free(label_ptr.cast());
+                label
+            };
+            let Some(label) = label else {
+                continue;
+            };
+
+            // These are the same `name: value` pairs that `sensors -u` would print, so
+            // we can build the sensor the same way. The values are stringified to
+            // share that path.
+            let mut subfields = Vec::new();
+            let mut subfeature_nr: c_int = 0;
+
+            loop {
+                // SAFETY: Same as above.
+                let subfeature =
+                    unsafe { (self.get_all_subfeatures)(chip, feature, &mut subfeature_nr) };
+                if subfeature.is_null() {
+                    break;
+                }
+
+                // SAFETY: `subfeature` is non-null and owned by libsensors.
+                let (subfeature_name, number, flags) = unsafe {
+                    (
+                        to_string((*subfeature).name),
+                        (*subfeature).number,
+                        (*subfeature).flags,
+                    )
+                };
+
+                if flags & SENSORS_MODE_R == 0 {
+                    continue;
+                }
+
+                let mut value: c_double = 0.0;
+
+                // SAFETY: `number` comes from the subfeature of this chip.
+                if unsafe { (self.get_value)(chip, number, &mut value) } == 0 {
+                    if let Some(subfeature_name) = subfeature_name {
+                        subfields.push((subfeature_name, value.to_string()));
+                    }
+                }
+            }
+
+            let subfields = subfields
+                .iter()
+                .map(|(key, value)| (key.as_str(), value.as_str()))
+                .collect::<Vec<_>>();
+
+            if let Some(sensor) = parse_lm_sensors_sensor(label, &subfields) {
+                sensors.push(sensor);
+            }
+        }
+
+        sensors
+    }
+}
+
+/// Returns all devices read through `libsensors`, or [`None`] if the library
+/// isn't available.
+pub(super) fn read_devices() -> Option<Vec<LmSensorsDevice>> {
+    // Loading happens inside `get_or_init`, so it's also only ever done by one
+    // thread at a time.
+    static LIBSENSORS: OnceLock<Option<Mutex<LibSensors>>> = OnceLock::new();
+
+    let libsensors = LIBSENSORS
+        .get_or_init(|| LibSensors::load().map(Mutex::new))
+        .as_ref()?;
+
+    // A panic while reading doesn't leave libsensors in a bad state, so a
+    // poisoned lock is still fine to use.
+    let libsensors = libsensors
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+    Some(libsensors.devices())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The label of each fake feature, by feature number. A missing label means
+    /// libsensors couldn't make one, and the feature should be skipped.
+    const LABELS: [Option<&[u8]>; 3] = [Some(b"SYSTIN\0"), None, Some(b"fan1\0")];
+
+    /// The subfeatures of each fake feature, as `(name, flags, value)`.
+    const SUBFEATURES: [&[(&[u8], c_uint, c_double)]; 3] = [
+        &[
+            (b"temp1_input\0", SENSORS_MODE_R, 32.0),
+            (b"temp1_max\0", SENSORS_MODE_R, 80.0),
+            // Not readable, so this shouldn't be used.
+            (b"temp1_crit\0", 0, 100.0),
+        ],
+        &[(b"temp2_input\0", SENSORS_MODE_R, 50.0)],
+        &[(b"fan1_input\0", SENSORS_MODE_R, 1200.0)],
+    ];
+
+    /// Subfeature numbers encode where the subfeature is in [`SUBFEATURES`].
+    fn subfeature_number(feature: usize, subfeature: usize) -> c_int {
+        (feature * 10 + subfeature) as c_int
+    }
+
+    // The fakes below leak what they hand out, as libsensors owns those and the
+    // caller never frees them.
+
+    unsafe extern "C" fn get_detected_chips(
+        _match: *const SensorsChipName, nr: *mut c_int,
+    ) -> *const SensorsChipName {
+        if *nr > 0 {
+            return ptr::null();
+        }
+        *nr += 1;
+
+        Box::leak(Box::new(SensorsChipName {
+            prefix: ptr::null_mut(),
+            bus: SensorsBusId { bus_type: 0, nr: 0 },
+            addr: 0x290,
+            path: ptr::null_mut(),
+        }))
+    }
+
+    unsafe extern "C" fn snprintf_chip_name(
+        buffer: *mut c_char, size: usize, _chip: *const SensorsChipName,
+    ) -> c_int {
+        let name = b"nct6798-isa-0290\0";
+        assert!(name.len() <= size);
+        ptr::copy_nonoverlapping(name.as_ptr().cast(), buffer, name.len());
+        (name.len() - 1) as c_int
+    }
+
+    unsafe extern "C" fn get_adapter_name(_bus: *const SensorsBusId) -> *const c_char {
+        b"ISA adapter\0".as_ptr().cast()
+    }
+
+    unsafe extern "C" fn get_features(
+        _chip: *const SensorsChipName, nr: *mut c_int,
+    ) -> *const SensorsFeature {
+        if *nr as usize >= LABELS.len() {
+            return ptr::null();
+        }
+        let number = *nr;
+        *nr += 1;
+
+        Box::leak(Box::new(SensorsFeature {
+            name: ptr::null_mut(),
+            number,
+            feature_type: 0,
+            first_subfeature: 0,
+            padding1: 0,
+        }))
+    }
+
+    unsafe extern "C" fn get_label(
+        _chip: *const SensorsChipName, feature: *const SensorsFeature,
+    ) -> *mut c_char {
+        match LABELS[(*feature).number as usize] {
+            Some(label) => libc::strdup(label.as_ptr().cast()),
+            None => ptr::null_mut(),
+        }
+    }
+
+    unsafe extern "C" fn get_all_subfeatures(
+        _chip: *const SensorsChipName, feature: *const SensorsFeature, nr: *mut c_int,
+    ) -> *const SensorsSubfeature {
+        let feature = (*feature).number as usize;
+        let Some((name, flags, _)) = SUBFEATURES[feature].get(*nr as usize) else {
+            return ptr::null();
+        };
+        let number = subfeature_number(feature, *nr as usize);
+        *nr += 1;
+
+        Box::leak(Box::new(SensorsSubfeature {
+            name: name.as_ptr().cast_mut().cast(),
+            number,
+            subfeature_type: 0,
+            mapping: 0,
+            flags: *flags,
+        }))
+    }
+
+    unsafe extern "C" fn get_value(
+        _chip: *const SensorsChipName, number: c_int, value: *mut c_double,
+    ) -> c_int {
+        let number = number as usize;
+        *value = SUBFEATURES[number / 10][number % 10].2;
+        0
+    }
+
+    #[test]
+    fn test_devices() {
+        let libsensors = LibSensors {
+            _library: libloading::os::unix::Library::this().into(),
+            get_detected_chips,
+            snprintf_chip_name,
+            get_adapter_name,
+            get_features,
+            get_label,
+            get_all_subfeatures,
+            get_value,
+        };
+
+        let devices = libsensors.devices();
+        assert_eq!(devices.len(), 1);
+
+        let device = &devices[0];
+        assert_eq!(device.name, "nct6798-isa-0290");
+        assert_eq!(device.adapter, "ISA adapter");
+
+        // The unlabelled feature is skipped, and the unreadable threshold is
+        // left out.
+        let sensors = &device.sensors;
+        assert_eq!(sensors.len(), 2);
+        assert_eq!(sensors[0].name, "SYSTIN");
+        assert_eq!(sensors[0].prefix, "temp1");
+        assert_eq!(sensors[0].value, Some(32.0));
+        assert_eq!(sensors[0].high, Some(80.0));
+        assert_eq!(sensors[0].crit, None);
+        assert_eq!(sensors[1].name, "fan1");
+        assert_eq!(sensors[1].value, Some(1200.0));
+    }
+}
@@ -0,0 +1,161 @@
+//! An async version of collecting from lm_sensors, for use from a tokio
+//! runtime without blocking it on `sensors`.
+
+use std::{path::Path, process::Stdio, time::Duration};
+
+use anyhow::{anyhow, Context, Result};
+use tokio::process::Command;
+
+use super::{
+    none_if_unavailable, parse_lm_sensors_data, parse_lm_sensors_json, record_json_support,
+    sort_temperatures_by_name, supports_json, unsupported_platform, LmSensorsDevice,
+    LmSensorsSnapshot, SensorsOutput, DEFAULT_SENSORS_PATH, DEFAULT_SENSORS_TIMEOUT,
+};
+use crate::{
+    app::filter::Filter,
+    data_collection::temperature::{TempHarvest, TemperatureType},
+};
+
+impl LmSensorsSnapshot {
+    /// Like [`LmSensorsSnapshot::with_timeout`], but runs `sensors` without
+    /// blocking. Dropping the future kills `sensors` if it's still running.
+    pub async fn with_timeout_async(timeout: Duration) -> Result<Self> {
+        let (devices, warning) = get_lm_sensor_data_async(None, timeout).await?;
+
+        Ok(Self {
+            warning,
+            ..Self::from_devices(devices)
+        })
+    }
+}
+
+/// Like [`get_temperature_data`](super::get_temperature_data), but runs
+/// `sensors` without blocking. Dropping the future kills `sensors` if it's still
+/// running.
+pub async fn get_temperature_data_async(
+    temp_type: &TemperatureType, filter: &Option<Filter>, sort_by_name: bool,
+) -> Result<Option<Vec<TempHarvest>>> {
+    let snapshot =
+        none_if_unavailable(LmSensorsSnapshot::with_timeout_async(DEFAULT_SENSORS_TIMEOUT).await)?;
+
+    Ok(snapshot.map(|snapshot| {
+        let mut temperatures = snapshot.temperatures(temp_type, filter);
+        if sort_by_name {
+            sort_temperatures_by_name(&mut temperatures);
+        }
+        temperatures
+    }))
+}
+
+/// The async version of [`get_lm_sensor_data`](super::get_lm_sensor_data).
+async fn get_lm_sensor_data_async(
+    sensors_path: Option<&Path>, timeout: Duration,
+) -> Result<(Vec<LmSensorsDevice>, Option<String>)> {
+    if cfg!(target_os = "windows") {
+        return Err(unsupported_platform());
+    }
+
+    // libsensors blocks (and can hang on a stuck i2c bus), so read it on the
+    // blocking pool under the same timeout as `sensors`.
+    #[cfg(unix)]
+    if sensors_path.is_none() {
+        let read = tokio::task::spawn_blocking(super::libsensors::read_devices);
+        let devices = tokio::time::timeout(timeout, read)
+            .await
+            .map_err(|_| anyhow!("timed out after {timeout:?}"))??;
+        if let Some(devices) = devices {
+            return Ok((devices, None));
+        }
+    }
+
+    let sensors_path = sensors_path.unwrap_or(Path::new(DEFAULT_SENSORS_PATH));
+
+    let mut json_warning = None;
+    if supports_json(sensors_path) != Some(false) {
+        let output = run_sensors_async(sensors_path, "-j", timeout).await?;
+        if record_json_support(sensors_path, &output.stdout) {
+            return Ok((parse_lm_sensors_json(&output.stdout), output.warning));
+        }
+        json_warning = output.warning;
+    }
+
+    let output = run_sensors_async(sensors_path, "-u", timeout).await?;
+    Ok((
+        parse_lm_sensors_data(&output.stdout),
+        output.warning.or(json_warning),
+    ))
+}
+
+/// The async version of [`run_sensors`](super::run_sensors).
+async fn run_sensors_async(
+    sensors_path: &Path, arg: &str, timeout: Duration,
+) -> Result<SensorsOutput> {
+    // `kill_on_drop` makes sure that `sensors` doesn't outlive a timed out or
+    // dropped future; tokio then reaps it in the background.
+    let output = Command::new(sensors_path)
+        .arg(arg)
+        .env("LC_ALL", "C")
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .output();
+
+    let output = tokio::time::timeout(timeout, output)
+        .await
+        .map_err(|_| anyhow!("`{}` timed out", sensors_path.display()))?
+        .with_context(|| format!("failed to run `{}`", sensors_path.display()))?;
+
+    Ok(SensorsOutput::new(sensors_path, &output))
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use indoc::indoc;
+
+    use super::{super::tests::stub_sensors, *};
+
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    #[test]
+    fn test_async_sensors_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let sensors_path = stub_sensors(
+            dir.path(),
+            indoc! {r#"
+                #!/bin/sh
+                [ "$1" = "-u" ] || exit 0
+                printf 'k10temp-pci-00c3\nAdapter: PCI adapter\nTctl:\n  temp1_input: 45.000\n'
+            "#},
+        );
+
+        let (devices, warning) = block_on(get_lm_sensor_data_async(
+            Some(&sensors_path),
+            DEFAULT_SENSORS_TIMEOUT,
+        ))
+        .unwrap();
+        assert_eq!(warning, None);
+        assert_eq!(devices.len(), 1);
+        assert_eq!(devices[0].name, "k10temp-pci-00c3");
+        assert_eq!(devices[0].sensors[0].value, Some(45.0));
+    }
+
+    #[test]
+    fn test_async_timeout() {
+        let dir = tempfile::tempdir().unwrap();
+        let sensors_path = stub_sensors(dir.path(), "#!/bin/sh\nexec sleep 10\n");
+
+        let start = std::time::Instant::now();
+        let result = block_on(get_lm_sensor_data_async(
+            Some(&sensors_path),
+            Duration::from_millis(100),
+        ));
+        assert!(result.is_err());
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+}
@@ -0,0 +1,147 @@
+//! Reading temperature sensors straight from the hwmon sysfs interface, so the
+//! `sensors` binary isn't needed at all.
+
+use std::{fs, path::Path};
+
+use anyhow::Result;
+
+use super::{parse_lm_sensors_sensor, LmSensorsDevice};
+
+/// Where the kernel exposes hwmon devices.
+pub(super) const HWMON_PATH: &str = "/sys/class/hwmon";
+
+/// Reads the temperature sensors of every hwmon device under `root`. Devices are
+/// named `<chip>-hwmon-<n>` (e.g. `nvme-hwmon-2`), as sysfs doesn't give the
+/// bus and address that `sensors` uses.
+pub(super) fn read_devices(root: &Path) -> Result<Vec<LmSensorsDevice>> {
+    let mut hwmons = fs::read_dir(root)?
+        .flatten()
+        .filter_map(|entry| {
+            let number = entry
+                .file_name()
+                .to_str()?
+                .strip_prefix("hwmon")?
+                .parse::<u32>()
+                .ok()?;
+
+            Some((number, entry.path()))
+        })
+        .collect::<Vec<_>>();
+
+    // Sort numerically so the order is stable, and hwmon10 comes after hwmon9.
+    hwmons.sort_by_key(|(number, _)| *number);
+
+    Ok(hwmons
+        .into_iter()
+        .filter_map(|(number, path)| {
+            let chip = read_trimmed(&path.join("name"))?;
+
+            Some(LmSensorsDevice {
+                name: format!("{chip}-hwmon-{number}"),
+                adapter: String::new(),
+                sensors: read_sensors(&path),
+            })
+        })
+        .collect())
+}
+
+fn read_sensors(hwmon: &Path) -> Vec<super::LmSensorsSensor> {
+    let Ok(entries) = fs::read_dir(hwmon) else {
+        return Vec::new();
+    };
+
+    let mut prefixes = entries
+        .flatten()
+        .filter_map(|entry| {
+            let file_name = entry.file_name();
+            let prefix = file_name.to_str()?.strip_suffix("_input")?;
+            let number = prefix.strip_prefix("temp")?.parse::<u32>().ok()?;
+
+            Some((number, prefix.to_string()))
+        })
+        .collect::<Vec<_>>();
+    prefixes.sort_by_key(|(number, _)| *number);
+
+    prefixes
+        .into_iter()
+        .filter_map(|(_, prefix)| {
+            let label =
+                read_trimmed(&hwmon.join(format!("{prefix}_label"))).unwrap_or(prefix.clone());
+
+            // Build the same `name: value` pairs that `sensors -u` would print, so the
+            // sensor is built the same way. Temperatures are in millidegrees.
+            let subfields = ["input", "max", "crit", "alarm"]
+                .into_iter()
+                .filter_map(|suffix| {
+                    let key = format!("{prefix}_{suffix}");
+                    let raw = read_trimmed(&hwmon.join(&key))?;
+                    let value = if suffix == "alarm" {
+                        raw
+                    } else {
+                        (raw.parse::<f32>().ok()? / 1000.0).to_string()
+                    };
+
+                    Some((key, value))
+                })
+                .collect::<Vec<_>>();
+
+            let subfields = subfields
+                .iter()
+                .map(|(key, value)| (key.as_str(), value.as_str()))
+                .collect::<Vec<_>>();
+
+            parse_lm_sensors_sensor(label, &subfields)
+        })
+        .collect()
+}
+
+fn read_trimmed(path: &Path) -> Option<String> {
+    fs::read_to_string(path)
+        .ok()
+        .map(|contents| contents.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_collection::temperature::write_sysfs_file;
+
+    #[test]
+    fn test_read_devices() {
+        let root = tempfile::tempdir().unwrap();
+
+        let write = |path, contents| write_sysfs_file(root.path(), path, contents);
+
+        write("hwmon10/name", "nvme\n");
+        write("hwmon10/temp1_input", "38850\n");
+        write("hwmon10/temp1_label", "Composite\n");
+        write("hwmon10/temp1_crit", "84850\n");
+
+        write("hwmon2/name", "k10temp\n");
+        write("hwmon2/temp1_input", "45125\n");
+        write("hwmon2/temp1_label", "Tctl\n");
+        write("hwmon2/temp3_input", "40000\n");
+        write("hwmon2/temp3_alarm", "1\n");
+        write("hwmon2/fan1_input", "1200\n");
+
+        // No name, so this isn't a usable device.
+        write("hwmon3/temp1_input", "30000\n");
+
+        let devices = read_devices(root.path()).unwrap();
+        assert_eq!(devices.len(), 2);
+
+        assert_eq!(devices[0].name, "k10temp-hwmon-2");
+        let sensors = &devices[0].sensors;
+        assert_eq!(sensors.len(), 2);
+        assert_eq!(sensors[0].name, "Tctl");
+        assert_eq!(sensors[0].value, Some(45.125));
+        assert_eq!(sensors[1].name, "temp3");
+        assert!(sensors[1].alarm);
+
+        assert_eq!(devices[1].name, "nvme-hwmon-10");
+        assert_eq!(devices[1].sensors[0].value, Some(38.85));
+        assert_eq!(devices[1].sensors[0].crit, Some(84.85));
+
+        assert!(read_devices(&root.path().join("missing")).is_err());
+    }
+}
@@ -0,0 +1,95 @@
+//! User-defined rules for naming sensors by the device they're on (e.g. `MB`
+//! for an `nct6798-isa-0290`), which take priority over the built-in names.
+
+use regex::{Regex, RegexBuilder};
+
+/// How a [`NameRule`] matches device names.
+#[derive(Clone, Debug)]
+enum NamePattern {
+    /// A lowercased substring.
+    Substring(String),
+    Regex(Regex),
+}
+
+/// Names the sensors of any device whose name matches, e.g. `nct6798-isa-0290`.
+/// Rules are checked in order, and the first match wins. Both kinds of rule
+/// match case-insensitively.
+#[derive(Clone, Debug)]
+pub struct NameRule {
+    pattern: NamePattern,
+    /// What to name the device, e.g. `MB` for sensors named like `MB: temp1`.
+    pub label: String,
+}
+
+impl NameRule {
+    /// Creates a rule that matches device names containing `pattern`.
+    pub fn substring(pattern: &str, label: impl Into<String>) -> Self {
+        Self {
+            pattern: NamePattern::Substring(pattern.to_lowercase()),
+            label: label.into(),
+        }
+    }
+
+    /// Creates a rule that matches device names matching the regex `pattern`.
+    /// Returns an error if the regex is invalid.
+    pub fn regex(pattern: &str, label: impl Into<String>) -> Result<Self, regex::Error> {
+        let regex = RegexBuilder::new(pattern).case_insensitive(true).build()?;
+
+        Ok(Self {
+            pattern: NamePattern::Regex(regex),
+            label: label.into(),
+        })
+    }
+
+    /// Returns whether the rule applies to a device.
+    pub fn is_match(&self, device_name: &str) -> bool {
+        match &self.pattern {
+            NamePattern::Substring(substring) => device_name.to_lowercase().contains(substring),
+            NamePattern::Regex(regex) => regex.is_match(device_name),
+        }
+    }
+
+    /// Returns the pattern as written, and whether it's a regex.
+    fn pattern(&self) -> (&str, bool) {
+        match &self.pattern {
+            NamePattern::Substring(substring) => (substring, false),
+            NamePattern::Regex(regex) => (regex.as_str(), true),
+        }
+    }
+}
+
+impl PartialEq for NameRule {
+    fn eq(&self, other: &Self) -> bool {
+        self.pattern() == other.pattern() && self.label == other.label
+    }
+}
+
+impl Eq for NameRule {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_name_rules() {
+        let rule = NameRule::substring("NCT67", "MB");
+        assert!(rule.is_match("nct6798-isa-0290"));
+        assert!(!rule.is_match("k10temp-pci-00c3"));
+
+        let rule = NameRule::regex("^nvme-pci-01", "SSD").unwrap();
+        assert!(rule.is_match("nvme-pci-0100"));
+        assert!(rule.is_match("NVME-pci-0100"));
+        assert!(!rule.is_match("nvme-pci-0200"));
+
+        assert!(NameRule::regex("nvme(", "SSD").is_err());
+
+        assert_eq!(
+            NameRule::regex("nvme", "SSD").unwrap(),
+            NameRule::regex("nvme", "SSD").unwrap()
+        );
+        assert_ne!(
+            NameRule::regex("nvme", "SSD").unwrap(),
+            NameRule::substring("nvme", "SSD")
+        );
+    }
+}
@@ -0,0 +1,134 @@
+//! Gets battery and charger temperatures from `/sys/class/power_supply`, which
+//! lm_sensors and hwmon often don't report.
+
+use std::{fs, ops::RangeInclusive, path::Path};
+
+use anyhow::Result;
+
+use super::{TempHarvest, TemperatureType};
+use crate::app::filter::Filter;
+
+/// Parses a power supply temperature, which is in tenths of a degree Celsius,
+/// and returns it in Celsius.
+fn parse_power_supply_temp(path: &Path) -> Result<f32> {
+    Ok(fs::read_to_string(path)?.trim_end().parse::<f32>()? / 10.0)
+}
+
+/// Returns whether the power supply also registered a hwmon device, in which
+/// case its temperature is already reported through hwmon.
+fn has_hwmon(supply: &Path) -> bool {
+    fs::read_dir(supply).is_ok_and(|entries| {
+        entries
+            .flatten()
+            .any(|entry| entry.file_name().to_string_lossy().starts_with("hwmon"))
+    })
+}
+
+fn power_supply_temperatures(
+    root: &Path, temp_type: &TemperatureType, filter: &Option<Filter>,
+    valid_range: Option<&RangeInclusive<f32>>,
+) -> Vec<TempHarvest> {
+    let Ok(read_dir) = root.read_dir() else {
+        return Vec::new();
+    };
+
+    let mut supplies = read_dir.flatten().collect::<Vec<_>>();
+    supplies.sort_by_key(|entry| entry.file_name());
+
+    supplies
+        .into_iter()
+        .filter_map(|entry| {
+            let supply = entry.path();
+            if has_hwmon(&supply) {
+                return None;
+            }
+
+            let supply_name = entry.file_name().to_string_lossy().into_owned();
+            let name = format!("Battery: {supply_name}");
+            if !Filter::optional_should_keep(filter, &name) {
+                return None;
+            }
+
+            // Most supplies (e.g. AC adapters) don't have a temperature at all.
+            let temp_celsius = parse_power_supply_temp(&supply.join("temp")).ok()?;
+
+            Some(TempHarvest {
+                name,
+                sensor_id: format!("power_supply/{supply_name}"),
+                temperature: temp_type.convert_valid_temp_unit(temp_celsius, valid_range),
+                unit: *temp_type,
+                ..Default::default()
+            })
+        })
+        .collect()
+}
+
+/// Appends battery and charger temperatures from `/sys/class/power_supply`. If
+/// `valid_range` is given, readings (in Celsius) outside of it are dropped.
+pub fn append_power_supply_temperatures(
+    temperatures: &mut Vec<TempHarvest>, temp_type: &TemperatureType, filter: &Option<Filter>,
+    valid_range: Option<&RangeInclusive<f32>>,
+) {
+    temperatures.extend(power_supply_temperatures(
+        Path::new("/sys/class/power_supply"),
+        temp_type,
+        filter,
+        valid_range,
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_collection::temperature::{write_sysfs_file, PLAUSIBLE_CELSIUS_RANGE};
+
+    #[test]
+    fn test_power_supply_temperatures() {
+        let root = tempfile::tempdir().unwrap();
+        let write = |path, contents| write_sysfs_file(root.path(), path, contents);
+
+        write("BAT0/temp", "312\n");
+        write("BAT1/temp", "-55\n");
+        write("AC/online", "1\n");
+        // Already reported through hwmon.
+        write("BAT2/temp", "300\n");
+        write("BAT2/hwmon4/name", "BAT2\n");
+
+        let temperatures =
+            power_supply_temperatures(root.path(), &TemperatureType::Celsius, &None, None);
+        let readings = temperatures
+            .iter()
+            .map(|temp| (temp.name.as_str(), temp.temperature))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            readings,
+            [("Battery: BAT0", Some(31.2)), ("Battery: BAT1", Some(-5.5))]
+        );
+        assert_eq!(temperatures[0].sensor_id, "power_supply/BAT0");
+
+        let temperatures =
+            power_supply_temperatures(root.path(), &TemperatureType::Fahrenheit, &None, None);
+        assert_eq!(temperatures[0].temperature, Some(88.16));
+
+        // Readings are only range checked if asked to.
+        write("BAT0/temp", "-620\n");
+        let temperatures =
+            power_supply_temperatures(root.path(), &TemperatureType::Celsius, &None, None);
+        assert_eq!(temperatures[0].temperature, Some(-62.0));
+        let temperatures = power_supply_temperatures(
+            root.path(),
+            &TemperatureType::Celsius,
+            &None,
+            Some(&PLAUSIBLE_CELSIUS_RANGE),
+        );
+        assert_eq!(temperatures[0].temperature, None);
+
+        assert!(power_supply_temperatures(
+            &root.path().join("missing"),
+            &TemperatureType::Celsius,
+            &None,
+            None
+        )
+        .is_empty());
+    }
+}
@@ -15,8 +15,11 @@ pub fn get_temperature_data(
 
         if Filter::optional_should_keep(filter, &name) {
             temperature_vec.push(TempHarvest {
+                sensor_id: format!("sysinfo/{name}"),
                 name,
                 temperature: Some(temp_type.convert_temp_unit(component.temperature())),
+                unit: *temp_type,
+                ..Default::default()
             });
         }
     }
@@ -33,12 +36,18 @@ pub fn get_temperature_data(
                 if let (Ok(name), Ok(temp)) = (ctl.name(), ctl.value()) {
                     if let Some(temp) = temp.as_temperature() {
                         temperature_vec.push(TempHarvest {
+                            sensor_id: format!("sysctl/{name}"),
                             name,
                             temperature: Some(match temp_type {
                                 TemperatureType::Celsius => temp.celsius(),
                                 TemperatureType::Kelvin => temp.kelvin(),
                                 TemperatureType::Fahrenheit => temp.fahrenheit(),
+                                TemperatureType::Rankine | TemperatureType::Reaumur => {
+                                    temp_type.convert_temp_unit(temp.celsius())
+                                }
                             }),
+                            unit: *temp_type,
+                            ..Default::default()
                         });
                     }
                 }
@@ -0,0 +1,191 @@
+//! Virtual sensors, which are computed from the readings of other sensors (e.g.
+//! the hottest of a set of per-core sensors).
+
+use std::str::FromStr;
+
+use super::TempHarvest;
+
+/// How a virtual sensor combines the readings of its sources.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Aggregation {
+    Max,
+    Min,
+    Avg,
+    /// The first source minus the second. This needs exactly two sources.
+    Delta,
+}
+
+impl FromStr for Aggregation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "max" => Ok(Aggregation::Max),
+            "min" => Ok(Aggregation::Min),
+            "avg" | "average" => Ok(Aggregation::Avg),
+            "delta" => Ok(Aggregation::Delta),
+            _ => Err(format!(
+                "'{s}' is an invalid aggregation, use one of: [max, min, avg, delta]."
+            )),
+        }
+    }
+}
+
+impl Aggregation {
+    /// Combines `values`, returning [`None`] if there are none, or if this is a
+    /// [`Aggregation::Delta`] and there aren't exactly two.
+    pub fn apply(&self, values: &[f32]) -> Option<f32> {
+        if values.is_empty() {
+            return None;
+        }
+
+        match self {
+            Aggregation::Max => values.iter().copied().reduce(f32::max),
+            Aggregation::Min => values.iter().copied().reduce(f32::min),
+            Aggregation::Avg => Some(values.iter().sum::<f32>() / values.len() as f32),
+            Aggregation::Delta => match values {
+                [first, second] => Some(first - second),
+                _ => None,
+            },
+        }
+    }
+}
+
+/// A user-defined sensor, computed from the readings of other sensors.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VirtualSensor {
+    pub name: String,
+    /// The sensors to read from, by name or by sensor ID.
+    pub sources: Vec<String>,
+    pub aggregation: Aggregation,
+}
+
+impl VirtualSensor {
+    /// Computes this sensor from `temps`. Returns [`None`] if any source is
+    /// missing or has no reading.
+    pub fn evaluate(&self, temps: &[TempHarvest]) -> Option<TempHarvest> {
+        let sources = self
+            .sources
+            .iter()
+            .map(|source| {
+                temps
+                    .iter()
+                    .find(|temp| temp.name == *source || temp.sensor_id == *source)
+                    .filter(|temp| temp.temperature.is_some())
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        let values = sources
+            .iter()
+            .filter_map(|temp| temp.temperature)
+            .collect::<Vec<_>>();
+
+        Some(TempHarvest {
+            name: self.name.clone(),
+            sensor_id: format!("virtual/{}", self.name),
+            temperature: Some(self.aggregation.apply(&values)?),
+            unit: sources.first().map(|temp| temp.unit).unwrap_or_default(),
+            timestamp: sources.first().and_then(|temp| temp.timestamp),
+            is_virtual: true,
+            ..Default::default()
+        })
+    }
+}
+
+/// Appends the virtual sensors computed from `temps` to it, in order. Virtual
+/// sensors can use earlier ones as sources, and any that can't be computed are
+/// skipped.
+pub fn append_virtual_sensors(temps: &mut Vec<TempHarvest>, virtual_sensors: &[VirtualSensor]) {
+    for virtual_sensor in virtual_sensors {
+        if let Some(temp) = virtual_sensor.evaluate(temps) {
+            temps.push(temp);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp(name: &str, temperature: Option<f32>) -> TempHarvest {
+        TempHarvest {
+            name: name.to_string(),
+            sensor_id: format!("chip/{name}"),
+            temperature,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_aggregations() {
+        let values = [40.0, 55.0, 45.0];
+
+        assert_eq!(Aggregation::Max.apply(&values), Some(55.0));
+        assert_eq!(Aggregation::Min.apply(&values), Some(40.0));
+        assert_eq!(Aggregation::Avg.apply(&values), Some(140.0 / 3.0));
+        assert_eq!(Aggregation::Delta.apply(&values), None);
+        assert_eq!(Aggregation::Delta.apply(&[55.0, 40.0]), Some(15.0));
+
+        for aggregation in [
+            Aggregation::Max,
+            Aggregation::Min,
+            Aggregation::Avg,
+            Aggregation::Delta,
+        ] {
+            assert_eq!(aggregation.apply(&[]), None);
+        }
+    }
+
+    #[test]
+    fn test_aggregation_from_str() {
+        assert_eq!(Aggregation::from_str("MAX"), Ok(Aggregation::Max));
+        assert_eq!(Aggregation::from_str("average"), Ok(Aggregation::Avg));
+        assert!(Aggregation::from_str("sum").is_err());
+    }
+
+    #[test]
+    fn test_virtual_sensors() {
+        let mut temps = vec![
+            temp("Core 0", Some(50.0)),
+            temp("Core 1", Some(60.0)),
+            temp("Ambient", Some(25.0)),
+            temp("Broken", None),
+        ];
+
+        let virtual_sensors = [
+            VirtualSensor {
+                name: "CPU max".to_string(),
+                sources: vec!["Core 0".to_string(), "chip/Core 1".to_string()],
+                aggregation: Aggregation::Max,
+            },
+            VirtualSensor {
+                name: "CPU over ambient".to_string(),
+                sources: vec!["CPU max".to_string(), "Ambient".to_string()],
+                aggregation: Aggregation::Delta,
+            },
+            VirtualSensor {
+                name: "Missing".to_string(),
+                sources: vec!["Core 0".to_string(), "Core 2".to_string()],
+                aggregation: Aggregation::Avg,
+            },
+            VirtualSensor {
+                name: "No reading".to_string(),
+                sources: vec!["Core 0".to_string(), "Broken".to_string()],
+                aggregation: Aggregation::Avg,
+            },
+        ];
+
+        append_virtual_sensors(&mut temps, &virtual_sensors);
+
+        let virtual_temps = temps
+            .iter()
+            .filter(|temp| temp.is_virtual)
+            .map(|temp| (temp.name.as_str(), temp.temperature))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            virtual_temps,
+            [("CPU max", Some(60.0)), ("CPU over ambient", Some(35.0))]
+        );
+        assert_eq!(temps[4].sensor_id, "virtual/CPU max");
+    }
+}
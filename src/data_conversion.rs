@@ -93,6 +93,7 @@ pub struct ConvertedData {
     pub battery_data: Vec<ConvertedBatteryData>,
     pub disk_data: Vec<DiskWidgetData>,
     pub temp_data: Vec<TempWidgetData>,
+    pub temp_warning: Option<String>,
 }
 
 impl ConvertedData {
@@ -127,6 +128,7 @@ impl ConvertedData {
 
     pub fn convert_temp_data(&mut self, data: &DataCollection, temperature_type: TemperatureType) {
         self.temp_data.clear();
+        self.temp_warning.clone_from(&data.temp_warning);
 
         data.temp_harvest.iter().for_each(|temp_harvest| {
             self.temp_data.push(TempWidgetData {
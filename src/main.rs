@@ -216,6 +216,8 @@ fn create_collection_thread(
     filters: DataFilters, used_widget_set: UsedWidgets,
 ) -> JoinHandle<()> {
     let temp_type = app_config_fields.temperature_type;
+    let virtual_sensors = app_config_fields.virtual_sensors.clone();
+    let name_rules = app_config_fields.name_rules.clone();
     let use_current_cpu_total = app_config_fields.use_current_cpu_total;
     let unnormalized_cpu = app_config_fields.unnormalized_cpu;
     let show_average_cpu = app_config_fields.show_average_cpu;
@@ -226,6 +228,8 @@ fn create_collection_thread(
 
         data_state.set_data_collection(used_widget_set);
         data_state.set_temperature_type(temp_type);
+        data_state.set_virtual_sensors(virtual_sensors);
+        data_state.set_name_rules(name_rules);
         data_state.set_use_current_cpu_total(use_current_cpu_total);
         data_state.set_unnormalized_cpu(unnormalized_cpu);
         data_state.set_show_average_cpu(show_average_cpu);
@@ -33,7 +33,7 @@ use crate::{
     app::{filter::Filter, layout_manager::*, *},
     canvas::components::time_chart::LegendPosition,
     constants::*,
-    data_collection::temperature::TemperatureType,
+    data_collection::temperature::{Aggregation, NameRule, TemperatureType, VirtualSensor},
     utils::data_units::DataUnit,
     widgets::*,
 };
@@ -276,6 +276,10 @@ pub(crate) fn init_app(
         update_rate: get_update_rate(args, config)?,
         temperature_type: get_temperature(args, config)
             .context("Update 'temperature_type' in your config file.")?,
+        virtual_sensors: get_virtual_sensors(config)
+            .context("Update 'temperature.virtual_sensors' in your config file.")?,
+        name_rules: get_name_rules(config)
+            .context("Update 'temperature.name_rules' in your config file.")?,
         show_average_cpu: get_show_average_cpu(args, config),
         use_dot: is_flag_enabled!(dot_marker, args.general, config),
         cpu_left_legend: is_flag_enabled!(cpu_left_legend, args.cpu, config),
@@ -695,6 +699,64 @@ fn get_temperature(args: &BottomArgs, config: &Config) -> OptionResult<Temperatu
     Ok(TemperatureType::Celsius)
 }
 
+/// Gets the virtual sensors. Delta sensors are checked here to have exactly two
+/// sources, as they'd otherwise never have a reading.
+fn get_virtual_sensors(config: &Config) -> OptionResult<Vec<VirtualSensor>> {
+    let Some(virtual_sensors) = config
+        .temperature
+        .as_ref()
+        .and_then(|temperature| temperature.virtual_sensors.as_ref())
+    else {
+        return Ok(Vec::new());
+    };
+
+    virtual_sensors
+        .iter()
+        .map(|virtual_sensor| {
+            let aggregation =
+                Aggregation::from_str(&virtual_sensor.aggregation).map_err(OptionError::config)?;
+
+            if aggregation == Aggregation::Delta && virtual_sensor.sources.len() != 2 {
+                return Err(OptionError::config(format!(
+                    "'{}' is a delta sensor, which needs exactly two sources, but has {}.",
+                    virtual_sensor.name,
+                    virtual_sensor.sources.len()
+                )));
+            }
+
+            Ok(VirtualSensor {
+                name: virtual_sensor.name.clone(),
+                sources: virtual_sensor.sources.clone(),
+                aggregation,
+            })
+        })
+        .collect()
+}
+
+/// Gets the friendly-name rules, in the order they're written. Regex rules are
+/// checked here so that a bad pattern is reported at startup.
+fn get_name_rules(config: &Config) -> OptionResult<Vec<NameRule>> {
+    let Some(name_rules) = config
+        .temperature
+        .as_ref()
+        .and_then(|temperature| temperature.name_rules.as_ref())
+    else {
+        return Ok(Vec::new());
+    };
+
+    name_rules
+        .iter()
+        .map(|rule| {
+            if rule.regex {
+                NameRule::regex(&rule.pattern, &rule.label)
+                    .map_err(|err| OptionError::config(err.to_string()))
+            } else {
+                Ok(NameRule::substring(&rule.pattern, &rule.label))
+            }
+        })
+        .collect()
+}
+
 /// Yes, this function gets whether to show average CPU (true) or not (false).
 fn get_show_average_cpu(args: &BottomArgs, config: &Config) -> bool {
     if args.cpu.hide_avg_cpu {
@@ -1008,7 +1070,11 @@ mod test {
         app::App,
         args::BottomArgs,
         options::{
-            config::flags::FlagConfig, get_default_time_value, get_retention, get_update_rate,
+            config::{
+                flags::FlagConfig,
+                temperature::{TempConfig, VirtualSensorConfig},
+            },
+            get_default_time_value, get_retention, get_update_rate, get_virtual_sensors,
             try_parse_ms,
         },
     };
@@ -1172,6 +1238,25 @@ mod test {
         assert_eq!(get_retention(&args, &config), Ok(600000));
     }
 
+    #[test]
+    fn config_delta_virtual_sensor_sources() {
+        let config_with_sources = |sources: &[&str]| Config {
+            temperature: Some(TempConfig {
+                virtual_sensors: Some(vec![VirtualSensorConfig {
+                    name: "CPU rise".to_string(),
+                    sources: sources.iter().map(|source| source.to_string()).collect(),
+                    aggregation: "delta".to_string(),
+                }]),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        assert!(get_virtual_sensors(&config_with_sources(&["Tctl", "Ambient"])).is_ok());
+        assert!(get_virtual_sensors(&config_with_sources(&["Tctl"])).is_err());
+        assert!(get_virtual_sensors(&config_with_sources(&["Tctl", "Ambient", "Tccd1"])).is_err());
+    }
+
     fn create_app(args: BottomArgs) -> App {
         let config = Config::default();
         super::init_app(args, config).unwrap().0
@@ -9,4 +9,38 @@ use super::IgnoreList;
 pub struct TempConfig {
     /// A filter over the sensor names.
     pub sensor_filter: Option<IgnoreList>,
+    /// Sensors computed from the readings of other sensors.
+    pub virtual_sensors: Option<Vec<VirtualSensorConfig>>,
+    /// Rules for naming sensors by the device they're on, checked in order
+    /// before the built-in names. Only used with lm_sensors.
+    pub name_rules: Option<Vec<NameRuleConfig>>,
+}
+
+/// A rule for naming the sensors of matching devices.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[cfg_attr(feature = "generate_schema", derive(schemars::JsonSchema))]
+#[cfg_attr(test, serde(deny_unknown_fields), derive(PartialEq, Eq))]
+pub struct NameRuleConfig {
+    /// The text to look for in device names (e.g. "nct6798"), case-insensitively.
+    #[serde(rename = "match")]
+    pub pattern: String,
+    /// Whether `match` is a regex rather than plain text. Defaults to false.
+    #[serde(default)]
+    pub regex: bool,
+    /// What to name the device, e.g. "MB" for sensors named like "MB: temp1".
+    pub label: String,
+}
+
+/// A sensor computed from the readings of other sensors.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[cfg_attr(feature = "generate_schema", derive(schemars::JsonSchema))]
+#[cfg_attr(test, serde(deny_unknown_fields), derive(PartialEq, Eq))]
+pub struct VirtualSensorConfig {
+    /// The name of the virtual sensor.
+    pub name: String,
+    /// The sensors to compute from, by name.
+    pub sources: Vec<String>,
+    /// How to combine the sources. One of "max", "min", "avg", or "delta" (the
+    /// first source minus the second).
+    pub aggregation: String,
 }
@@ -1,14 +1,12 @@
 use std::{borrow::Cow, cmp::max, num::NonZeroU16};
 
-use concat_string::concat_string;
-
 use crate::{
     app::AppConfigFields,
     canvas::components::data_table::{
         ColumnHeader, DataTableColumn, DataTableProps, DataTableStyling, DataToCell, SortColumn,
         SortDataTable, SortDataTableProps, SortOrder, SortsRow,
     },
-    data_collection::temperature::TemperatureType,
+    data_collection::temperature::{TemperatureType, UnitStyle},
     options::config::style::ColourPalette,
     utils::general::sort_partial_fn,
 };
@@ -37,14 +35,10 @@ impl ColumnHeader for TempWidgetColumn {
 impl TempWidgetData {
     pub fn temperature(&self) -> Cow<'static, str> {
         match self.temperature_value {
-            Some(temp_val) => {
-                let temp_type = match self.temperature_type {
-                    TemperatureType::Celsius => "°C",
-                    TemperatureType::Kelvin => "K",
-                    TemperatureType::Fahrenheit => "°F",
-                };
-                concat_string!(temp_val.to_string(), temp_type).into()
-            }
+            Some(temp_val) => self
+                .temperature_type
+                .format_temp(temp_val, UnitStyle::Symbol)
+                .into(),
             None => "N/A".to_string().into(),
         }
     }
@@ -133,8 +127,15 @@ impl TempWidgetState {
         self.force_update_data = true;
     }
 
-    /// Update the current table data.
-    pub fn set_table_data(&mut self, data: &[TempWidgetData]) {
+    /// Update the current table data. If there's a warning from collecting it,
+    /// the title says that some sensors failed.
+    pub fn set_table_data(&mut self, data: &[TempWidgetData], warning: Option<&str>) {
+        self.table.props.title = Some(if warning.is_some() {
+            " Temperatures (some sensors failed) ".into()
+        } else {
+            " Temperatures ".into()
+        });
+
         let mut data = data.to_vec();
         if let Some(column) = self.table.columns.get(self.table.sort_index()) {
             column.sort_by(&mut data, self.table.order());